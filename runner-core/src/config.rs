@@ -0,0 +1,557 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::presence::{Activity, ActivityKind};
+
+/// Title shown when the app doesn't pass `--title`.
+pub const DEFAULT_TITLE: &str = "Discord Quest Completer";
+
+pub const USAGE: &str = "\
+Stands in for a game's executable so Discord detects the game as running.
+
+Usage: <runner> [OPTIONS]
+
+Options:
+      --title <TEXT>        Window and tray title [default: Discord Quest Completer]
+      --app-id <ID>         Discord application id of the game, for the status file
+                            and --presence
+      --tray                Start hidden in the tray instead of showing the window
+      --duration <SECS>     Exit after running for this many seconds
+      --pid-file <PATH>     Write the process id to PATH while running
+      --status-file <PATH>  Keep PATH updated with a heartbeat and the exit reason
+      --parent-pid <PID>    Exit when the process PID is gone
+      --log-file <PATH>     Append log messages to PATH
+      --control-socket <PATH>
+                            Answer control requests on a Unix socket at PATH
+
+Rich Presence, when built with the `presence` feature:
+      --presence            Set Rich Presence for --app-id while running
+      --details <TEXT>      First line under the game's name
+      --state <TEXT>        Second line under the game's name
+      --large-image <KEY>   Asset key of the large image
+      --large-image-text <TEXT>
+                            Tooltip of the large image
+      --start-timestamp <SECS>
+                            Unix time the elapsed timer counts from
+      --activity-kind <CODE>
+                            0 playing, 2 listening, 3 watching, 5 competing [default: 0]
+
+  -h, --help                Print this help";
+
+/// How the runner was asked to start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub title: String,
+    pub app_id: Option<u64>,
+    /// Set Rich Presence for `app_id` while running (`--presence`).
+    pub presence: bool,
+    /// Start hidden in the tray instead of showing the window (`--tray`).
+    pub start_minimized: bool,
+    pub duration: Option<Duration>,
+    pub pid_file: Option<PathBuf>,
+    pub status_file: Option<PathBuf>,
+    pub parent_pid: Option<u32>,
+    pub log_file: Option<PathBuf>,
+    pub control_socket: Option<PathBuf>,
+    pub activity: Activity,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            title: DEFAULT_TITLE.to_string(),
+            app_id: None,
+            presence: false,
+            start_minimized: false,
+            duration: None,
+            pid_file: None,
+            status_file: None,
+            parent_pid: None,
+            log_file: None,
+            control_socket: None,
+            activity: Activity::default(),
+        }
+    }
+}
+
+/// What the command line asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run(Box<Config>),
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgsError {
+    UnknownFlag(String),
+    UnexpectedArgument(String),
+    MissingValue(&'static str),
+    /// A switch like `--tray` was given a value with `=`.
+    UnexpectedValue(&'static str),
+    InvalidValue {
+        flag: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+    Duplicate(&'static str),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsError::UnknownFlag(flag) => write!(f, "Unknown option {:?}", flag),
+            ArgsError::UnexpectedArgument(arg) => write!(f, "Unexpected argument {:?}", arg),
+            ArgsError::MissingValue(flag) => write!(f, "{} requires a value", flag),
+            ArgsError::UnexpectedValue(flag) => write!(f, "{} doesn't take a value", flag),
+            ArgsError::InvalidValue {
+                flag,
+                value,
+                reason,
+            } => write!(f, "Invalid value {:?} for {}: {}", value, flag, reason),
+            ArgsError::Duplicate(flag) => write!(f, "{} was given more than once", flag),
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+const FLAGS: [&str; 18] = [
+    "--title",
+    "--app-id",
+    "--tray",
+    "--duration",
+    "--pid-file",
+    "--status-file",
+    "--parent-pid",
+    "--log-file",
+    "--control-socket",
+    "--presence",
+    "--details",
+    "--state",
+    "--large-image",
+    "--large-image-text",
+    "--start-timestamp",
+    "--activity-kind",
+    "--help",
+    "-h",
+];
+
+/// Stores `value` in `slot` unless the flag was already given.
+fn set_once<T>(slot: &mut Option<T>, flag: &'static str, value: T) -> Result<(), ArgsError> {
+    if slot.is_some() {
+        return Err(ArgsError::Duplicate(flag));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn parse_positive(flag: &'static str, value: &str) -> Result<u64, ArgsError> {
+    match value.parse::<u64>() {
+        Ok(0) => Err(ArgsError::InvalidValue {
+            flag,
+            value: value.to_string(),
+            reason: "must be greater than 0",
+        }),
+        Ok(number) => Ok(number),
+        Err(_) => Err(ArgsError::InvalidValue {
+            flag,
+            value: value.to_string(),
+            reason: "expected a whole number",
+        }),
+    }
+}
+
+fn parse_activity_kind(flag: &'static str, value: &str) -> Result<ActivityKind, ArgsError> {
+    match value {
+        "0" | "2" | "3" | "5" => Ok(ActivityKind::from_code(value.parse().unwrap_or(0))),
+        _ => Err(ArgsError::InvalidValue {
+            flag,
+            value: value.to_string(),
+            reason: "expected 0, 2, 3 or 5",
+        }),
+    }
+}
+
+fn parse_path(flag: &'static str, value: String) -> Result<PathBuf, ArgsError> {
+    if value.is_empty() {
+        return Err(ArgsError::InvalidValue {
+            flag,
+            value,
+            reason: "must not be empty",
+        });
+    }
+    Ok(PathBuf::from(value))
+}
+
+/// Parses the runner's arguments, without the program name, on top of `defaults`.
+/// Values can be given as `--flag value` or `--flag=value`.
+pub fn parse_args(
+    args: impl IntoIterator<Item = String>,
+    defaults: Config,
+) -> Result<Command, ArgsError> {
+    let mut config = defaults;
+    let mut args = args.into_iter();
+
+    let mut title = None;
+    let mut app_id = None;
+    let mut tray = None;
+    let mut presence = None;
+    let mut duration = None;
+    let mut pid_file = None;
+    let mut status_file = None;
+    let mut parent_pid = None;
+    let mut log_file = None;
+    let mut control_socket = None;
+    let mut details = None;
+    let mut state = None;
+    let mut large_image = None;
+    let mut large_image_text = None;
+    let mut start_timestamp = None;
+    let mut activity_kind = None;
+
+    while let Some(arg) = args.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let Some(&flag) = FLAGS.iter().find(|flag| **flag == name) else {
+            if name.starts_with('-') {
+                return Err(ArgsError::UnknownFlag(name.to_string()));
+            }
+            return Err(ArgsError::UnexpectedArgument(arg));
+        };
+
+        match flag {
+            "--help" | "-h" | "--tray" | "--presence" if inline.is_some() => {
+                return Err(ArgsError::UnexpectedValue(flag))
+            }
+            "--help" | "-h" => return Ok(Command::Help),
+            "--tray" => set_once(&mut tray, flag, ())?,
+            "--presence" => set_once(&mut presence, flag, ())?,
+            _ => {
+                let value = match inline {
+                    Some(value) => value,
+                    // A following flag is never taken as the value, so `--title --tray`
+                    // is reported instead of producing a window called "--tray".
+                    None => match args.next() {
+                        Some(value) if !value.starts_with("--") => value,
+                        _ => return Err(ArgsError::MissingValue(flag)),
+                    },
+                };
+                match flag {
+                    "--title" => set_once(&mut title, flag, value)?,
+                    "--app-id" => set_once(&mut app_id, flag, parse_positive(flag, &value)?)?,
+                    "--duration" => {
+                        let secs = parse_positive(flag, &value)?;
+                        set_once(&mut duration, flag, Duration::from_secs(secs))?
+                    }
+                    "--pid-file" => set_once(&mut pid_file, flag, parse_path(flag, value)?)?,
+                    "--status-file" => set_once(&mut status_file, flag, parse_path(flag, value)?)?,
+                    "--parent-pid" => {
+                        let pid = parse_positive(flag, &value)?;
+                        let pid = u32::try_from(pid).map_err(|_| ArgsError::InvalidValue {
+                            flag,
+                            value,
+                            reason: "not a process id",
+                        })?;
+                        set_once(&mut parent_pid, flag, pid)?
+                    }
+                    "--log-file" => set_once(&mut log_file, flag, parse_path(flag, value)?)?,
+                    "--control-socket" => {
+                        set_once(&mut control_socket, flag, parse_path(flag, value)?)?
+                    }
+                    "--details" => set_once(&mut details, flag, value)?,
+                    "--state" => set_once(&mut state, flag, value)?,
+                    "--large-image" => set_once(&mut large_image, flag, value)?,
+                    "--large-image-text" => set_once(&mut large_image_text, flag, value)?,
+                    "--start-timestamp" => {
+                        let secs = parse_positive(flag, &value)?;
+                        let secs = i64::try_from(secs).map_err(|_| ArgsError::InvalidValue {
+                            flag,
+                            value,
+                            reason: "too large",
+                        })?;
+                        set_once(&mut start_timestamp, flag, secs)?
+                    }
+                    "--activity-kind" => {
+                        set_once(&mut activity_kind, flag, parse_activity_kind(flag, &value)?)?
+                    }
+                    _ => unreachable!("{} is listed in FLAGS", flag),
+                }
+            }
+        }
+    }
+
+    if let Some(title) = title {
+        config.title = title;
+    }
+    config.app_id = app_id.or(config.app_id);
+    config.presence |= presence.is_some();
+    config.start_minimized |= tray.is_some();
+    config.duration = duration.or(config.duration);
+    config.pid_file = pid_file.or(config.pid_file);
+    config.status_file = status_file.or(config.status_file);
+    config.parent_pid = parent_pid.or(config.parent_pid);
+    config.log_file = log_file.or(config.log_file);
+    config.control_socket = control_socket.or(config.control_socket);
+    let activity = config.activity;
+    config.activity = Activity {
+        kind: activity_kind.unwrap_or(activity.kind),
+        details: details.or(activity.details),
+        state: state.or(activity.state),
+        large_image_key: large_image.or(activity.large_image_key),
+        large_image_text: large_image_text.or(activity.large_image_text),
+        start_timestamp: start_timestamp.or(activity.start_timestamp),
+    }
+    .normalized();
+
+    Ok(Command::Run(Box::new(config)))
+}
+
+/// Parses the process arguments for a runner binary. Prints the help and exits on
+/// `--help`, and exits with status 2 on bad input.
+pub fn from_env_or_exit(defaults: Config) -> Config {
+    match parse_args(std::env::args().skip(1), defaults) {
+        Ok(Command::Run(config)) => *config,
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Error: {}\n\nRun with --help for usage.", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, ArgsError> {
+        parse_args(args.iter().map(|arg| arg.to_string()), Config::default())
+    }
+
+    fn config(args: &[&str]) -> Config {
+        match parse(args) {
+            Ok(Command::Run(config)) => *config,
+            other => panic!("expected a config for {:?}, got {:?}", args, other),
+        }
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        assert_eq!(config(&[]), Config::default());
+    }
+
+    #[test]
+    fn reads_title_and_tray() {
+        let config = config(&[
+            "--title",
+            "Some Game",
+            "--tray",
+            "--app-id",
+            "1158877933042143272",
+        ]);
+        assert_eq!(config.title, "Some Game");
+        assert_eq!(config.app_id, Some(1158877933042143272));
+        assert!(config.start_minimized);
+    }
+
+    #[test]
+    fn reads_lifecycle_flags() {
+        let config = config(&[
+            "--duration",
+            "900",
+            "--pid-file",
+            "/tmp/runner.pid",
+            "--status-file=/tmp/status.json",
+            "--parent-pid",
+            "4242",
+            "--log-file",
+            "runner.log",
+            "--control-socket",
+            "/tmp/runner.sock",
+        ]);
+        assert_eq!(config.duration, Some(Duration::from_secs(900)));
+        assert_eq!(config.pid_file, Some(PathBuf::from("/tmp/runner.pid")));
+        assert_eq!(config.status_file, Some(PathBuf::from("/tmp/status.json")));
+        assert_eq!(config.parent_pid, Some(4242));
+        assert_eq!(config.log_file, Some(PathBuf::from("runner.log")));
+        assert_eq!(
+            config.control_socket,
+            Some(PathBuf::from("/tmp/runner.sock"))
+        );
+    }
+
+    #[test]
+    fn reads_activity_flags() {
+        assert!(!config(&["--app-id", "42"]).presence);
+        let config = config(&[
+            "--app-id",
+            "42",
+            "--presence",
+            "--details",
+            "In a match",
+            "--state=",
+            "--large-image",
+            "logo",
+            "--large-image-text",
+            "Logo",
+            "--start-timestamp",
+            "1700000000",
+            "--activity-kind",
+            "5",
+        ]);
+        assert_eq!(
+            config.activity,
+            Activity {
+                kind: ActivityKind::Competing,
+                details: Some("In a match".to_string()),
+                state: None,
+                large_image_key: Some("logo".to_string()),
+                large_image_text: Some("Logo".to_string()),
+                start_timestamp: Some(1_700_000_000),
+            }
+        );
+        assert!(config.presence);
+        assert!(matches!(
+            parse(&["--activity-kind", "1"]),
+            Err(ArgsError::InvalidValue {
+                flag: "--activity-kind",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn accepts_inline_values() {
+        assert_eq!(config(&["--title=A = B"]).title, "A = B");
+    }
+
+    #[test]
+    fn titles_may_start_with_a_single_dash() {
+        assert_eq!(config(&["--title", "-1 Game"]).title, "-1 Game");
+    }
+
+    #[test]
+    fn inline_titles_may_start_with_dashes() {
+        assert_eq!(config(&["--title=--Game"]).title, "--Game");
+        assert_eq!(config(&["--title=-1 Game"]).title, "-1 Game");
+    }
+
+    #[test]
+    fn help_wins_over_other_flags() {
+        assert_eq!(parse(&["--title", "Game", "--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["-h"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn rejects_missing_values() {
+        assert_eq!(parse(&["--title"]), Err(ArgsError::MissingValue("--title")));
+        assert_eq!(
+            parse(&["--title", "--tray"]),
+            Err(ArgsError::MissingValue("--title"))
+        );
+        assert_eq!(
+            parse(&["--duration"]),
+            Err(ArgsError::MissingValue("--duration"))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_flags_and_arguments() {
+        assert_eq!(
+            parse(&["--unknown"]),
+            Err(ArgsError::UnknownFlag("--unknown".to_string()))
+        );
+        assert_eq!(
+            parse(&["-x"]),
+            Err(ArgsError::UnknownFlag("-x".to_string()))
+        );
+        assert_eq!(
+            parse(&["extra"]),
+            Err(ArgsError::UnexpectedArgument("extra".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_numbers() {
+        for args in [
+            &["--duration", "0"][..],
+            &["--duration", "-5"],
+            &["--duration", "1.5"],
+            &["--parent-pid", "abc"],
+            &["--parent-pid", "0"],
+            &["--parent-pid", "99999999999"],
+            &["--app-id", "0"],
+        ] {
+            assert!(
+                matches!(parse(args), Err(ArgsError::InvalidValue { .. })),
+                "{:?} should be rejected",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_empty_paths() {
+        assert!(matches!(
+            parse(&["--pid-file="]),
+            Err(ArgsError::InvalidValue {
+                flag: "--pid-file",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_values_on_switches() {
+        assert_eq!(
+            parse(&["--tray=yes"]),
+            Err(ArgsError::UnexpectedValue("--tray"))
+        );
+        assert_eq!(
+            parse(&["--presence=1"]),
+            Err(ArgsError::UnexpectedValue("--presence"))
+        );
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        assert_eq!(
+            parse(&["--title", "A", "--title", "B"]),
+            Err(ArgsError::Duplicate("--title"))
+        );
+        assert_eq!(
+            parse(&["--tray", "--tray"]),
+            Err(ArgsError::Duplicate("--tray"))
+        );
+    }
+
+    #[test]
+    fn builds_on_custom_defaults() {
+        let defaults = Config {
+            title: "Runner".to_string(),
+            start_minimized: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            parse_args(Vec::new(), defaults.clone()),
+            Ok(Command::Run(Box::new(defaults)))
+        );
+    }
+
+    #[test]
+    fn errors_read_well() {
+        assert_eq!(
+            ArgsError::MissingValue("--title").to_string(),
+            "--title requires a value"
+        );
+        assert_eq!(
+            parse(&["--duration", "soon"]).unwrap_err().to_string(),
+            "Invalid value \"soon\" for --duration: expected a whole number"
+        );
+    }
+}
@@ -0,0 +1,452 @@
+//! Local control channel between the app and a runner.
+//!
+//! The app connects to `--control-socket` and sends one JSON object per line; the runner
+//! answers each with one line. Every message carries `"version"`, requests from a newer
+//! protocol are refused rather than guessed at.
+//!
+//! Only Unix has a transport so far. Windows runners have no control channel: the app
+//! doesn't pass `--control-socket` there and ends them with `TerminateProcess`.
+//!
+//! ```text
+//! > {"version":1,"command":"ping"}
+//! < {"version":1,"ok":true}
+//! > {"version":1,"command":"status"}
+//! < {"version":1,"ok":true,"pid":4242,"title":"Game","uptime_secs":12,"duration_secs":900,"remaining_secs":888}
+//! > {"version":1,"command":"rename_title","title":"Other Game"}
+//! < {"version":1,"ok":true,"title":"Other Game"}
+//! > {"version":1,"command":"shutdown"}
+//! < {"version":1,"ok":true}
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::config::Config;
+use crate::json;
+use crate::log;
+use crate::session::{ExitReason, Session, Shutdown};
+
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// Longest title `rename_title` accepts, in characters.
+pub const MAX_TITLE_CHARS: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Ping,
+    Status,
+    RenameTitle(String),
+    Shutdown,
+}
+
+/// Reads one request line.
+pub fn parse_request(line: &str) -> Result<Request, String> {
+    let object = json::parse_object(line)?;
+
+    match object.u64("version") {
+        Some(PROTOCOL_VERSION) => {}
+        Some(version) => return Err(format!("Unsupported protocol version {}", version)),
+        None => return Err("Missing protocol version".to_string()),
+    }
+
+    match object.str("command") {
+        Some("ping") => Ok(Request::Ping),
+        Some("status") => Ok(Request::Status),
+        Some("rename_title") => {
+            let title = object
+                .str("title")
+                .ok_or_else(|| "rename_title requires a title".to_string())?;
+            if title.trim().is_empty() {
+                return Err("Title must not be empty".to_string());
+            }
+            if title.chars().count() > MAX_TITLE_CHARS {
+                return Err(format!(
+                    "Title is longer than {} characters",
+                    MAX_TITLE_CHARS
+                ));
+            }
+            Ok(Request::RenameTitle(title.to_string()))
+        }
+        Some("shutdown") => Ok(Request::Shutdown),
+        Some(command) => Err(format!("Unknown command {:?}", command)),
+        None => Err("Missing command".to_string()),
+    }
+}
+
+fn respond_ok(fields: &[(&str, String)]) -> String {
+    let mut response = format!("{{\"version\":{},\"ok\":true", PROTOCOL_VERSION);
+    for (name, value) in fields {
+        response.push_str(&format!(",{}:{}", json::string(name), value));
+    }
+    response.push('}');
+    response
+}
+
+fn respond_error(message: &str) -> String {
+    format!(
+        "{{\"version\":{},\"ok\":false,\"error\":{}}}",
+        PROTOCOL_VERSION,
+        json::string(message)
+    )
+}
+
+type Hook = Box<dyn Fn(&str) + Send + Sync>;
+
+/// What the control channel can see and change in the runner.
+pub struct Controls {
+    session: Session,
+    shutdown: Shutdown,
+    title: Mutex<String>,
+    on_rename: Option<Hook>,
+    wake: Option<Box<dyn Fn() + Send + Sync>>,
+    /// Set by `shutdown`; the wake waits until the reply is sent, since the runner may
+    /// exit right after it.
+    wake_pending: AtomicBool,
+}
+
+impl Controls {
+    pub fn new(config: &Config, session: Session, shutdown: Shutdown) -> Self {
+        Controls {
+            session,
+            shutdown,
+            title: Mutex::new(config.title.clone()),
+            on_rename: None,
+            wake: None,
+            wake_pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Called with the new title after `rename_title`, to update the window.
+    pub fn on_rename(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_rename = Some(Box::new(hook));
+        self
+    }
+
+    /// Called once the reply to `shutdown` is sent, so the event loop notices.
+    pub fn wake(mut self, wake: impl Fn() + Send + Sync + 'static) -> Self {
+        self.wake = Some(Box::new(wake));
+        self
+    }
+
+    pub fn handle(&self, request: Request, now: Instant) -> String {
+        match request {
+            Request::Ping => respond_ok(&[]),
+            Request::Status => {
+                let title = self.title.lock().unwrap().clone();
+                respond_ok(&[
+                    ("pid", std::process::id().to_string()),
+                    ("title", json::string(&title)),
+                    (
+                        "uptime_secs",
+                        self.session.elapsed_at(now).as_secs().to_string(),
+                    ),
+                    (
+                        "duration_secs",
+                        json::optional(self.session.duration().map(|d| d.as_secs())),
+                    ),
+                    (
+                        "remaining_secs",
+                        json::optional(self.session.remaining_at(now).map(|d| d.as_secs())),
+                    ),
+                ])
+            }
+            Request::RenameTitle(title) => {
+                log!("Renaming to {:?}", title);
+                *self.title.lock().unwrap() = title.clone();
+                if let Some(hook) = &self.on_rename {
+                    hook(&title);
+                }
+                respond_ok(&[("title", json::string(&title))])
+            }
+            Request::Shutdown => {
+                if self.shutdown.request(ExitReason::ControlShutdown) {
+                    log!("Shutdown requested over the control socket");
+                    self.wake_pending.store(true, Ordering::SeqCst);
+                }
+                respond_ok(&[])
+            }
+        }
+    }
+
+    /// Answers one request line.
+    pub fn handle_line(&self, line: &str) -> String {
+        match parse_request(line) {
+            Ok(request) => self.handle(request, Instant::now()),
+            Err(e) => respond_error(&e),
+        }
+    }
+
+    /// Runs the wake left by `shutdown`. Call it after writing the reply.
+    pub fn wake_if_pending(&self) {
+        if self.wake_pending.swap(false, Ordering::SeqCst) {
+            if let Some(wake) = &self.wake {
+                wake();
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+pub use self::unix::{serve, ControlSocket};
+
+#[cfg(unix)]
+mod unix {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::Controls;
+    use crate::log;
+
+    /// A client that stops talking is dropped after this long.
+    const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Longest request line accepted, in bytes.
+    const MAX_LINE: u64 = 4096;
+
+    /// Clients served at once; more are turned away until one disconnects.
+    const MAX_CLIENTS: usize = 8;
+
+    /// Holds the listening socket file and removes it on drop.
+    #[derive(Debug)]
+    pub struct ControlSocket {
+        path: PathBuf,
+    }
+
+    impl Drop for ControlSocket {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    fn serve_client(stream: UnixStream, controls: &Controls) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+
+        loop {
+            let mut line = String::new();
+            if (&mut reader).take(MAX_LINE).read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let response = if line.ends_with('\n') {
+                controls.handle_line(line.trim_end())
+            } else {
+                super::respond_error("Request line too long or unterminated")
+            };
+            let written = writer
+                .write_all(response.as_bytes())
+                .and_then(|_| writer.write_all(b"\n"));
+            controls.wake_if_pending();
+            written?;
+            if !line.ends_with('\n') {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Listens on `path` and answers each client on its own thread, so one that stops
+    /// talking can't hold up a `shutdown` from another. A stale socket left by a crashed
+    /// runner is replaced. Only the current user can connect.
+    pub fn serve(path: &Path, controls: Controls) -> std::io::Result<ControlSocket> {
+        if std::fs::symlink_metadata(path).is_ok() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        let socket = ControlSocket {
+            path: path.to_path_buf(),
+        };
+
+        let controls = Arc::new(controls);
+        let clients = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        log!("Failed to accept a control client: {}", e);
+                        continue;
+                    }
+                };
+                if clients.fetch_add(1, Ordering::SeqCst) >= MAX_CLIENTS {
+                    clients.fetch_sub(1, Ordering::SeqCst);
+                    log!("Too many control clients, dropping one");
+                    continue;
+                }
+                let controls = Arc::clone(&controls);
+                let clients = Arc::clone(&clients);
+                std::thread::spawn(move || {
+                    if let Err(e) = serve_client(stream, &controls) {
+                        log!("Control client failed: {}", e);
+                    }
+                    clients.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        Ok(socket)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn controls() -> (Controls, Shutdown) {
+        let shutdown = Shutdown::new();
+        let config = Config {
+            title: "Game".to_string(),
+            ..Config::default()
+        };
+        let session = Session::start(Some(Duration::from_secs(900)));
+        (Controls::new(&config, session, shutdown.clone()), shutdown)
+    }
+
+    #[test]
+    fn parses_requests() {
+        assert_eq!(
+            parse_request(r#"{"version":1,"command":"ping"}"#),
+            Ok(Request::Ping)
+        );
+        assert_eq!(
+            parse_request(r#"{"command":"rename_title","title":"New","version":1}"#),
+            Ok(Request::RenameTitle("New".to_string()))
+        );
+        assert_eq!(
+            parse_request(r#"{"version":1,"command":"shutdown"}"#),
+            Ok(Request::Shutdown)
+        );
+    }
+
+    #[test]
+    fn refuses_other_versions_and_unknown_commands() {
+        assert_eq!(
+            parse_request(r#"{"version":2,"command":"ping"}"#),
+            Err("Unsupported protocol version 2".to_string())
+        );
+        assert!(parse_request(r#"{"command":"ping"}"#).is_err());
+        assert!(parse_request(r#"{"version":1,"command":"reboot"}"#).is_err());
+        assert!(parse_request(r#"{"version":1,"command":"rename_title"}"#).is_err());
+        assert!(parse_request(r#"{"version":1,"command":"rename_title","title":" "}"#).is_err());
+        assert!(parse_request("ping").is_err());
+    }
+
+    #[test]
+    fn reports_status() {
+        let (controls, _) = controls();
+        let response =
+            json::parse_object(&controls.handle_line(r#"{"version":1,"command":"status"}"#))
+                .unwrap();
+        assert_eq!(response.get("ok"), Some(&json::Value::Bool(true)));
+        assert_eq!(response.u64("pid"), Some(u64::from(std::process::id())));
+        assert_eq!(response.str("title"), Some("Game"));
+        assert_eq!(response.u64("duration_secs"), Some(900));
+        assert!(response.u64("uptime_secs").is_some());
+    }
+
+    #[test]
+    fn renames_and_calls_the_hook() {
+        let (controls, _) = controls();
+        let renamed = Arc::new(Mutex::new(String::new()));
+        let seen = Arc::clone(&renamed);
+        let controls = controls.on_rename(move |title| *seen.lock().unwrap() = title.to_string());
+
+        let response = controls.handle(Request::RenameTitle("Other".to_string()), Instant::now());
+        assert_eq!(response, r#"{"version":1,"ok":true,"title":"Other"}"#);
+        let status = controls.handle(Request::Status, Instant::now());
+        assert!(status.contains(r#""title":"Other""#));
+        assert_eq!(*renamed.lock().unwrap(), "Other");
+    }
+
+    #[test]
+    fn shutdown_records_the_reason_and_wakes_once() {
+        let (controls, shutdown) = controls();
+        let wakes = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&wakes);
+        let controls = controls.wake(move || *counter.lock().unwrap() += 1);
+
+        controls.handle(Request::Shutdown, Instant::now());
+        assert_eq!(shutdown.reason(), Some(ExitReason::ControlShutdown));
+        assert_eq!(*wakes.lock().unwrap(), 0);
+
+        controls.wake_if_pending();
+        controls.handle(Request::Shutdown, Instant::now());
+        controls.wake_if_pending();
+        assert_eq!(*wakes.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn errors_are_reported_in_band() {
+        let (controls, _) = controls();
+        assert_eq!(
+            controls.handle_line(r#"{"version":1,"command":"reboot"}"#),
+            r#"{"version":1,"ok":false,"error":"Unknown command \"reboot\""}"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn serves_clients_over_a_socket() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        let path = std::env::temp_dir().join(format!("runner-core-{}.sock", std::process::id()));
+        let (controls, shutdown) = controls();
+        let socket = serve(&path, controls).unwrap();
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+
+        stream
+            .write_all(
+                b"{\"version\":1,\"command\":\"ping\"}\n{\"version\":1,\"command\":\"shutdown\"}\n",
+            )
+            .unwrap();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"version\":1,\"ok\":true}\n");
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"version\":1,\"ok\":true}\n");
+        assert_eq!(shutdown.reason(), Some(ExitReason::ControlShutdown));
+
+        drop(socket);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn silent_clients_dont_block_others() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        let path =
+            std::env::temp_dir().join(format!("runner-core-silent-{}.sock", std::process::id()));
+        let (controls, shutdown) = controls();
+        let _socket = serve(&path, controls).unwrap();
+
+        // Connects and never sends anything.
+        let _silent = UnixStream::connect(&path).unwrap();
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        stream
+            .write_all(b"{\"version\":1,\"command\":\"shutdown\"}\n")
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        assert_eq!(line, "{\"version\":1,\"ok\":true}\n");
+        assert_eq!(shutdown.reason(), Some(ExitReason::ControlShutdown));
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::log;
+use crate::session::{ExitReason, Session};
+use crate::status::{Status, StatusWriter, HEARTBEAT_INTERVAL};
+
+/// Holds `--pid-file` for as long as the runner runs and removes it on drop.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    pub fn create(path: &Path) -> std::io::Result<PidFile> {
+        std::fs::write(path, format!("{}\n", std::process::id()))?;
+        Ok(PidFile {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log!("Failed to remove pid file {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Files the runner keeps while it runs. Keep it alive until the event loop returns.
+#[derive(Debug)]
+pub struct RunnerFiles {
+    _pid_file: Option<PidFile>,
+    status: Option<StatusWriter>,
+}
+
+impl RunnerFiles {
+    /// Opens the log file, writes the pid file and starts the status heartbeat. Failures
+    /// are logged rather than fatal, the runner is still useful without them.
+    pub fn create(config: &Config) -> RunnerFiles {
+        if let Some(path) = &config.log_file {
+            if let Err(e) = log::init(path) {
+                log!("Failed to open log file {:?}: {}", path, e);
+            }
+        }
+        log!("Starting runner {:?}", config.title);
+
+        let pid_file = config
+            .pid_file
+            .as_deref()
+            .and_then(|path| match PidFile::create(path) {
+                Ok(pid_file) => Some(pid_file),
+                Err(e) => {
+                    log!("Failed to write pid file {:?}: {}", path, e);
+                    None
+                }
+            });
+
+        let status = config.status_file.as_deref().map(|path| {
+            let status = Status::running(config.app_id, &config.title);
+            StatusWriter::start(path, status, HEARTBEAT_INTERVAL)
+        });
+
+        RunnerFiles {
+            _pid_file: pid_file,
+            status,
+        }
+    }
+
+    /// Writes why the runner exited to `--status-file` and removes the pid file.
+    pub fn finish(self, reason: Option<ExitReason>, session: &Session) {
+        let runtime = session.elapsed();
+        log!(
+            "Exiting after {}s: {}",
+            runtime.as_secs(),
+            reason.map_or("unknown", ExitReason::as_str)
+        );
+
+        if let Some(status) = self.status {
+            status.finish(reason, runtime);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pid_file_lives_as_long_as_its_guard() {
+        let path = std::env::temp_dir().join(format!("runner-core-{}.pid", std::process::id()));
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{}\n", std::process::id())
+        );
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,341 @@
+//! Just enough JSON for the status file, the control protocol and Discord's IPC replies,
+//! so runner-core stays free of dependencies.
+
+use std::fmt::Write as _;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Object),
+}
+
+/// How deeply objects and arrays may nest before the input is refused.
+const MAX_DEPTH: usize = 32;
+
+/// Fields of a JSON object, in order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Object(Vec<(String, Value)>);
+
+impl Object {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn object(&self, key: &str) -> Option<&Object> {
+        match self.get(key) {
+            Some(Value::Object(object)) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn str(&self, key: &str) -> Option<&str> {
+        match self.get(key) {
+            Some(Value::String(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// A whole, non-negative number.
+    pub fn u64(&self, key: &str) -> Option<u64> {
+        match self.get(key) {
+            Some(Value::Number(number))
+                if *number >= 0.0 && number.fract() == 0.0 && *number <= u64::MAX as f64 =>
+            {
+                Some(*number as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Quotes `text` as a JSON string.
+pub fn string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Writes `value` as JSON, or `null` when it is `None`.
+pub fn optional(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected {:?}, found {:?}", expected, c)),
+            None => Err(format!("Expected {:?}, found the end", expected)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('/') => out.push('/'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('u') => out.push(self.unicode_escape()?),
+                    other => return Err(format!("Invalid escape {:?}", other)),
+                },
+                Some(c) if u32::from(c) < 0x20 => {
+                    return Err("Control character in string".to_string())
+                }
+                Some(c) => out.push(c),
+                None => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.chars.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| format!("Invalid \\u escape {:?}", digits))
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            // Surrogate pair, the low half follows as another \u escape.
+            if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                return Err("Unpaired surrogate".to_string());
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err("Unpaired surrogate".to_string());
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| "Unpaired surrogate".to_string())
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'))
+        {
+            word.push(c);
+        }
+        word
+    }
+
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        if self.depth == MAX_DEPTH {
+            return Err("Nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn array(&mut self) -> Result<Vec<Value>, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(items),
+                other => return Err(format!("Expected ',' or ']', found {:?}", other)),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('"') => self.string().map(Value::String),
+            Some('{') => self.nested(Self::object).map(Value::Object),
+            Some('[') => self.nested(Self::array).map(Value::Array),
+            Some(_) => match self.word().as_str() {
+                "null" => Ok(Value::Null),
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                word => word
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|number| number.is_finite() && !word.starts_with(['+', '.']))
+                    .map(Value::Number)
+                    .ok_or_else(|| format!("Invalid value {:?}", word)),
+            },
+            None => Err("Expected a value, found the end".to_string()),
+        }
+    }
+
+    fn object(&mut self) -> Result<Object, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            let value = self.value()?;
+            fields.push((key, value));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Object(fields)),
+                other => return Err(format!("Expected ',' or '}}', found {:?}", other)),
+            }
+        }
+    }
+}
+
+/// Parses a JSON object such as `{"command":"ping","version":1}`.
+pub fn parse_object(text: &str) -> Result<Object, String> {
+    let mut parser = Parser {
+        chars: text.chars().peekable(),
+        depth: 0,
+    };
+    let object = parser.object()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None => Ok(object),
+        Some(c) => Err(format!("Unexpected {:?} after the object", c)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(string("Game"), "\"Game\"");
+        assert_eq!(
+            string("A \"quoted\" \\ name\n\u{1}"),
+            "\"A \\\"quoted\\\" \\\\ name\\n\\u0001\""
+        );
+        assert_eq!(string("Pokémon"), "\"Pokémon\"");
+    }
+
+    #[test]
+    fn reads_flat_objects() {
+        let object =
+            parse_object(r#" { "command": "rename_title", "title": "A \"B\" é🎮", "version": 1, "x": null, "y": false } "#)
+                .unwrap();
+        assert_eq!(object.str("command"), Some("rename_title"));
+        assert_eq!(object.str("title"), Some("A \"B\" é🎮"));
+        assert_eq!(object.u64("version"), Some(1));
+        assert_eq!(object.get("x"), Some(&Value::Null));
+        assert_eq!(object.get("y"), Some(&Value::Bool(false)));
+        assert_eq!(object.get("missing"), None);
+        assert_eq!(parse_object("{}").unwrap(), Object::default());
+    }
+
+    #[test]
+    fn reads_nested_values() {
+        let object = parse_object(
+            r#"{"evt":"ERROR","data":{"code":4000,"message":"Bad"},"list":[1,"two",[],{}]}"#,
+        )
+        .unwrap();
+        let data = object.object("data").unwrap();
+        assert_eq!(data.u64("code"), Some(4000));
+        assert_eq!(data.str("message"), Some("Bad"));
+        assert_eq!(
+            object.get("list"),
+            Some(&Value::Array(vec![
+                Value::Number(1.0),
+                Value::String("two".to_string()),
+                Value::Array(Vec::new()),
+                Value::Object(Object::default()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn refuses_deep_nesting() {
+        let deep = format!("{{\"a\":{}{}}}", "[".repeat(100), "]".repeat(100));
+        assert_eq!(parse_object(&deep), Err("Nested too deeply".to_string()));
+    }
+
+    #[test]
+    fn round_trips_strings() {
+        let text = "Tab\tquote\" slash\\ bell\u{7} 🎮";
+        let object = parse_object(&format!("{{\"title\":{}}}", string(text))).unwrap();
+        assert_eq!(object.str("title"), Some(text));
+    }
+
+    #[test]
+    fn only_whole_numbers_are_u64() {
+        let object = parse_object(r#"{"a":1.5,"b":-1,"c":"1","d":2e3}"#).unwrap();
+        assert_eq!(object.u64("a"), None);
+        assert_eq!(object.u64("b"), None);
+        assert_eq!(object.u64("c"), None);
+        assert_eq!(object.u64("d"), Some(2000));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for text in [
+            "",
+            "[]",
+            "{",
+            r#"{"a"}"#,
+            r#"{"a":}"#,
+            r#"{"a":1,}"#,
+            r#"{"a":[1,]}"#,
+            r#"{"a":{"b"}}"#,
+            r#"{"a":"unterminated}"#,
+            r#"{"a":nope}"#,
+            r#"{"a":1} trailing"#,
+            r#"{"a":"\ud83c"}"#,
+        ] {
+            assert!(parse_object(text).is_err(), "{:?} should be rejected", text);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Logic shared by the runner binaries that stand in for a game's executable.
+//!
+//! Everything here is platform-neutral so it builds and tests anywhere; the binaries
+//! (`src-win`, `src-linux`) only own their window, tray icon and event loop.
+
+pub mod config;
+pub mod control;
+pub mod files;
+pub mod json;
+pub mod lifecycle;
+pub mod log;
+pub mod presence;
+pub mod session;
+pub mod status;
+pub mod watchdog;
+
+pub use config::{from_env_or_exit, parse_args, ArgsError, Command, Config};
+pub use control::Controls;
+pub use files::RunnerFiles;
+pub use lifecycle::{Action, Lifecycle, MenuCommand, Visibility};
+pub use presence::{Activity, ActivityKind, Presence};
+pub use session::{ExitReason, Session, Shutdown};
+pub use status::Status;
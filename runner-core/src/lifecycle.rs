@@ -0,0 +1,134 @@
+use crate::config::Config;
+use crate::session::{ExitReason, Shutdown};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Shown,
+    /// Only the tray icon is visible; the window is out of the taskbar and Alt-Tab.
+    Hidden,
+}
+
+/// Entries of the tray menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuCommand {
+    Show,
+    Hide,
+    Quit,
+}
+
+/// What the binary has to do to its window after a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Bring the hidden window back into the taskbar and show it.
+    Show,
+    /// The window is already shown; just bring it to the front.
+    Focus,
+    /// Hide the window to the tray.
+    Hide,
+    /// Leave the event loop.
+    Quit,
+    Nothing,
+}
+
+/// Show/hide state of the runner's window.
+#[derive(Debug, Clone)]
+pub struct Lifecycle {
+    visibility: Visibility,
+    shutdown: Shutdown,
+}
+
+impl Lifecycle {
+    pub fn new(config: &Config, shutdown: Shutdown) -> Self {
+        let visibility = if config.start_minimized {
+            Visibility::Hidden
+        } else {
+            Visibility::Shown
+        };
+        Lifecycle {
+            visibility,
+            shutdown,
+        }
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// What to do with the freshly created window.
+    pub fn initial_action(&self) -> Action {
+        match self.visibility {
+            Visibility::Shown => Action::Show,
+            Visibility::Hidden => Action::Hide,
+        }
+    }
+
+    pub fn handle(&mut self, command: MenuCommand) -> Action {
+        if self.shutdown.is_requested() {
+            return Action::Quit;
+        }
+
+        match (command, self.visibility) {
+            (MenuCommand::Show, Visibility::Hidden) => {
+                self.visibility = Visibility::Shown;
+                Action::Show
+            }
+            (MenuCommand::Show, Visibility::Shown) => Action::Focus,
+            (MenuCommand::Hide, Visibility::Shown) => {
+                self.visibility = Visibility::Hidden;
+                Action::Hide
+            }
+            (MenuCommand::Hide, Visibility::Hidden) => Action::Nothing,
+            (MenuCommand::Quit, _) => {
+                self.shutdown.request(ExitReason::UserQuit);
+                Action::Quit
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lifecycle(start_minimized: bool) -> Lifecycle {
+        let config = Config {
+            start_minimized,
+            ..Config::default()
+        };
+        Lifecycle::new(&config, Shutdown::new())
+    }
+
+    #[test]
+    fn starts_hidden_with_tray_flag() {
+        assert_eq!(lifecycle(true).initial_action(), Action::Hide);
+        assert_eq!(lifecycle(false).initial_action(), Action::Show);
+    }
+
+    #[test]
+    fn toggles_between_shown_and_hidden() {
+        let mut lifecycle = lifecycle(true);
+        assert_eq!(lifecycle.handle(MenuCommand::Hide), Action::Nothing);
+        assert_eq!(lifecycle.handle(MenuCommand::Show), Action::Show);
+        assert_eq!(lifecycle.visibility(), Visibility::Shown);
+        assert_eq!(lifecycle.handle(MenuCommand::Show), Action::Focus);
+        assert_eq!(lifecycle.handle(MenuCommand::Hide), Action::Hide);
+        assert_eq!(lifecycle.visibility(), Visibility::Hidden);
+    }
+
+    #[test]
+    fn quit_requests_shutdown() {
+        let shutdown = Shutdown::new();
+        let mut lifecycle = Lifecycle::new(&Config::default(), shutdown.clone());
+        assert_eq!(lifecycle.handle(MenuCommand::Quit), Action::Quit);
+        assert_eq!(shutdown.reason(), Some(ExitReason::UserQuit));
+    }
+
+    #[test]
+    fn any_command_quits_once_shutdown_was_requested() {
+        let shutdown = Shutdown::new();
+        let mut lifecycle = Lifecycle::new(&Config::default(), shutdown.clone());
+        shutdown.request(ExitReason::WindowClosed);
+        assert_eq!(lifecycle.handle(MenuCommand::Show), Action::Quit);
+        assert_eq!(shutdown.reason(), Some(ExitReason::WindowClosed));
+    }
+}
@@ -0,0 +1,38 @@
+//! Minimal logging for the runners: stderr, plus the `--log-file` when one was given.
+//! The Windows runner has no console, so the file is the only way to see its output.
+
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Starts appending to `path`. Only the first call has an effect.
+pub fn init(path: &Path) -> std::io::Result<()> {
+    let file = File::options().create(true).append(true).open(path)?;
+    let _ = LOG_FILE.set(Mutex::new(file));
+    Ok(())
+}
+
+pub fn write(message: fmt::Arguments) {
+    eprintln!("{}", message);
+    if let Some(file) = LOG_FILE.get() {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut file = file.lock().unwrap();
+        let _ = writeln!(file, "[{}] [{}] {}", secs, std::process::id(), message);
+    }
+}
+
+/// Logs a `format!`-style message, see [`write`].
+#[macro_export]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        $crate::log::write(format_args!($($arg)*))
+    };
+}
@@ -0,0 +1,485 @@
+//! Discord Rich Presence.
+//!
+//! [`Activity`] is shared with the app, which sends it through discord-sdk. Runners built
+//! with the `presence` feature set it themselves over Discord's local IPC socket, which
+//! needs no dependencies: each frame is a little-endian opcode and length followed by JSON.
+//! The activity stays up while the connection is open and is cleared on exit.
+
+use crate::config::Config;
+use crate::json;
+
+pub const OP_HANDSHAKE: u32 = 0;
+pub const OP_FRAME: u32 = 1;
+pub const OP_CLOSE: u32 = 2;
+
+/// Longest frame accepted from Discord.
+pub const MAX_FRAME: u32 = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActivityKind {
+    #[default]
+    Playing,
+    Listening,
+    Watching,
+    Competing,
+}
+
+impl ActivityKind {
+    /// Discord's activity type codes, as used by the app's activity JSON. Unknown codes
+    /// fall back to playing.
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            2 => ActivityKind::Listening,
+            3 => ActivityKind::Watching,
+            5 => ActivityKind::Competing,
+            _ => ActivityKind::Playing,
+        }
+    }
+
+    pub fn code(self) -> i32 {
+        match self {
+            ActivityKind::Playing => 0,
+            ActivityKind::Listening => 2,
+            ActivityKind::Watching => 3,
+            ActivityKind::Competing => 5,
+        }
+    }
+}
+
+/// What the presence shows besides the game's name.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Activity {
+    pub kind: ActivityKind,
+    pub details: Option<String>,
+    pub state: Option<String>,
+    pub large_image_key: Option<String>,
+    /// Only shown together with `large_image_key`.
+    pub large_image_text: Option<String>,
+    pub start_timestamp: Option<i64>,
+}
+
+fn non_empty(text: Option<String>) -> Option<String> {
+    text.filter(|text| !text.is_empty())
+}
+
+impl Activity {
+    /// Treats empty strings as not set, the way the app always has.
+    pub fn normalized(self) -> Self {
+        Activity {
+            details: non_empty(self.details),
+            state: non_empty(self.state),
+            large_image_key: non_empty(self.large_image_key),
+            large_image_text: non_empty(self.large_image_text),
+            ..self
+        }
+    }
+
+    /// The activity object of a `SET_ACTIVITY` request.
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![format!("\"type\":{}", self.kind.code())];
+        if let Some(details) = &self.details {
+            fields.push(format!("\"details\":{}", json::string(details)));
+        }
+        if let Some(state) = &self.state {
+            fields.push(format!("\"state\":{}", json::string(state)));
+        }
+        if let Some(start) = self.start_timestamp {
+            fields.push(format!("\"timestamps\":{{\"start\":{}}}", start));
+        }
+        if let Some(key) = &self.large_image_key {
+            let mut assets = format!("\"large_image\":{}", json::string(key));
+            if let Some(text) = &self.large_image_text {
+                assets.push_str(&format!(",\"large_text\":{}", json::string(text)));
+            }
+            fields.push(format!("\"assets\":{{{}}}", assets));
+        }
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Prefixes `payload` with its opcode and length.
+pub fn encode_frame(op: u32, payload: &str) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.extend_from_slice(&op.to_le_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload.as_bytes());
+    frame
+}
+
+pub fn handshake(app_id: u64) -> String {
+    format!(
+        "{{\"v\":1,\"client_id\":{}}}",
+        json::string(&app_id.to_string())
+    )
+}
+
+/// A `SET_ACTIVITY` request for process `pid`; `None` clears the activity.
+pub fn set_activity(pid: u32, activity: Option<&Activity>, nonce: u64) -> String {
+    let activity = activity
+        .map(|activity| format!(",\"activity\":{}", activity.to_json()))
+        .unwrap_or_default();
+    format!(
+        "{{\"cmd\":\"SET_ACTIVITY\",\"args\":{{\"pid\":{}{}}},\"nonce\":\"{}\"}}",
+        pid, activity, nonce
+    )
+}
+
+/// The error Discord answered with, if any.
+pub fn reply_error(op: u32, payload: &str) -> Option<String> {
+    let reply = match json::parse_object(payload) {
+        Ok(reply) => reply,
+        Err(e) => return Some(format!("Unreadable reply: {}", e)),
+    };
+    let message = |object: &json::Object| object.str("message").unwrap_or("no message").to_string();
+    if op == OP_CLOSE {
+        return Some(format!(
+            "Discord closed the connection: {}",
+            message(&reply)
+        ));
+    }
+    if reply.str("evt") == Some("ERROR") {
+        let data = reply.object("data").cloned().unwrap_or_default();
+        return Some(message(&data));
+    }
+    None
+}
+
+/// Keeps the runner's activity up until [`Presence::clear`].
+#[derive(Debug)]
+pub struct Presence {
+    #[cfg(feature = "presence")]
+    inner: std::sync::Arc<std::sync::Mutex<ipc::Inner>>,
+}
+
+/// Sets the activity for `--app-id` on a background thread, retrying until Discord is
+/// running. Returns `None` unless `--presence` was given with an app id.
+#[cfg(feature = "presence")]
+pub fn start(config: &Config) -> Option<Presence> {
+    if !config.presence {
+        return None;
+    }
+    let app_id = config.app_id?;
+    let inner = ipc::start(app_id, config.activity.clone());
+    Some(Presence { inner })
+}
+
+#[cfg(not(feature = "presence"))]
+pub fn start(config: &Config) -> Option<Presence> {
+    use crate::log;
+
+    if config.presence {
+        log!("Built without the presence feature, ignoring --presence");
+    }
+    None
+}
+
+impl Presence {
+    /// Clears the activity and closes the connection. Call it before exiting.
+    pub fn clear(self) {
+        #[cfg(feature = "presence")]
+        ipc::clear(&self.inner);
+    }
+}
+
+#[cfg(feature = "presence")]
+mod ipc {
+    use std::io::{self, Read, Write};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{encode_frame, handshake, reply_error, set_activity, Activity, MAX_FRAME};
+    use super::{OP_FRAME, OP_HANDSHAKE};
+    use crate::log;
+
+    /// How long to wait before trying again when Discord isn't running.
+    const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+    #[cfg(unix)]
+    type Stream = std::os::unix::net::UnixStream;
+    #[cfg(windows)]
+    type Stream = std::fs::File;
+
+    #[cfg(unix)]
+    fn connect() -> io::Result<Stream> {
+        let dirs = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(std::path::PathBuf::from)
+            .chain([std::path::PathBuf::from("/tmp")]);
+        for dir in dirs {
+            // Flatpak and Snap builds of Discord put the socket in a subfolder.
+            for sub in ["", "app/com.discordapp.Discord", "snap.discord"] {
+                for index in 0..10 {
+                    let path = dir.join(sub).join(format!("discord-ipc-{}", index));
+                    if let Ok(stream) = Stream::connect(&path) {
+                        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+                        stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+                        return Ok(stream);
+                    }
+                }
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Discord is not running",
+        ))
+    }
+
+    /// The pipe has no read timeout. Only the background thread waits for replies, and
+    /// the runner never waits for that thread.
+    #[cfg(windows)]
+    fn connect() -> io::Result<Stream> {
+        for index in 0..10 {
+            let path = format!(r"\\?\pipe\discord-ipc-{}", index);
+            if let Ok(pipe) = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+            {
+                return Ok(pipe);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Discord is not running",
+        ))
+    }
+
+    fn read_frame(stream: &mut Stream) -> io::Result<(u32, String)> {
+        let mut header = [0u8; 8];
+        stream.read_exact(&mut header)?;
+        let op = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if len > MAX_FRAME {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Frame of {} bytes is too large", len),
+            ));
+        }
+        let mut payload = vec![0u8; len as usize];
+        stream.read_exact(&mut payload)?;
+        Ok((op, String::from_utf8_lossy(&payload).into_owned()))
+    }
+
+    enum Failure {
+        /// Discord isn't running or the connection broke; worth another try.
+        Unreachable(String),
+        /// Discord answered with an error, e.g. to an unknown client id. It gives the same
+        /// answer to the same app id and activity, so there is no point retrying.
+        Refused(String),
+    }
+
+    struct Connection {
+        stream: Stream,
+        nonce: u64,
+    }
+
+    impl Connection {
+        fn send(&mut self, op: u32, payload: &str) -> Result<(), String> {
+            self.stream
+                .write_all(&encode_frame(op, payload))
+                .map_err(|e| format!("Failed to write to Discord: {}", e))
+        }
+
+        fn request(&mut self, op: u32, payload: &str) -> Result<(), Failure> {
+            self.send(op, payload).map_err(Failure::Unreachable)?;
+            let (op, reply) = read_frame(&mut self.stream)
+                .map_err(|e| Failure::Unreachable(format!("Discord didn't answer: {}", e)))?;
+            match reply_error(op, &reply) {
+                Some(e) => Err(Failure::Refused(e)),
+                None => Ok(()),
+            }
+        }
+
+        fn open(app_id: u64) -> Result<Connection, Failure> {
+            let stream = connect().map_err(|e| Failure::Unreachable(e.to_string()))?;
+            let mut connection = Connection { stream, nonce: 0 };
+            connection.request(OP_HANDSHAKE, &handshake(app_id))?;
+            Ok(connection)
+        }
+
+        fn set(&mut self, activity: &Activity) -> Result<(), Failure> {
+            self.nonce += 1;
+            let payload = set_activity(std::process::id(), Some(activity), self.nonce);
+            self.request(OP_FRAME, &payload)
+        }
+
+        /// Doesn't wait for the reply: the pipe has no read timeout on Windows, and an
+        /// unresponsive Discord must not keep the runner from exiting. Discord also drops
+        /// the activity once the connection closes.
+        fn clear(&mut self) -> Result<(), String> {
+            self.nonce += 1;
+            let payload = set_activity(std::process::id(), None, self.nonce);
+            self.send(OP_FRAME, &payload)
+        }
+    }
+
+    #[derive(Default)]
+    pub(super) struct Inner {
+        connection: Option<Connection>,
+        cleared: bool,
+    }
+
+    impl std::fmt::Debug for Inner {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Inner")
+                .field("connected", &self.connection.is_some())
+                .field("cleared", &self.cleared)
+                .finish()
+        }
+    }
+
+    pub(super) fn start(app_id: u64, activity: Activity) -> Arc<Mutex<Inner>> {
+        let inner = Arc::new(Mutex::new(Inner::default()));
+        let shared = Arc::clone(&inner);
+
+        std::thread::spawn(move || {
+            let mut reported = false;
+            loop {
+                if shared.lock().unwrap().cleared {
+                    return;
+                }
+                let result = Connection::open(app_id).and_then(|mut connection| {
+                    connection.set(&activity)?;
+                    Ok(connection)
+                });
+                match result {
+                    Ok(connection) => {
+                        let mut inner = shared.lock().unwrap();
+                        if !inner.cleared {
+                            log!("Set Discord activity for app {}", app_id);
+                            inner.connection = Some(connection);
+                        }
+                        return;
+                    }
+                    Err(Failure::Refused(e)) => {
+                        log!("Discord refused the activity, giving up: {}", e);
+                        return;
+                    }
+                    Err(Failure::Unreachable(e)) => {
+                        if !reported {
+                            log!("Failed to set Discord activity, retrying: {}", e);
+                            reported = true;
+                        }
+                        std::thread::sleep(RETRY_INTERVAL);
+                    }
+                }
+            }
+        });
+
+        inner
+    }
+
+    pub(super) fn clear(inner: &Mutex<Inner>) {
+        let mut inner = inner.lock().unwrap();
+        inner.cleared = true;
+        if let Some(mut connection) = inner.connection.take() {
+            match connection.clear() {
+                Ok(()) => log!("Cleared Discord activity"),
+                Err(e) => log!("Failed to clear Discord activity: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity() -> Activity {
+        Activity {
+            kind: ActivityKind::Watching,
+            details: Some("In \"menu\"".to_string()),
+            state: Some("Idle".to_string()),
+            large_image_key: Some("logo".to_string()),
+            large_image_text: Some("Logo".to_string()),
+            start_timestamp: Some(1_700_000_000),
+        }
+    }
+
+    #[test]
+    fn maps_activity_codes() {
+        for kind in [
+            ActivityKind::Playing,
+            ActivityKind::Listening,
+            ActivityKind::Watching,
+            ActivityKind::Competing,
+        ] {
+            assert_eq!(ActivityKind::from_code(kind.code()), kind);
+        }
+        assert_eq!(ActivityKind::from_code(1), ActivityKind::Playing);
+        assert_eq!(ActivityKind::from_code(-1), ActivityKind::Playing);
+    }
+
+    #[test]
+    fn drops_empty_fields() {
+        let activity = Activity {
+            details: Some(String::new()),
+            state: Some("State".to_string()),
+            large_image_key: Some(String::new()),
+            ..Activity::default()
+        }
+        .normalized();
+        assert_eq!(activity.details, None);
+        assert_eq!(activity.state.as_deref(), Some("State"));
+        assert_eq!(activity.large_image_key, None);
+    }
+
+    #[test]
+    fn serializes_the_activity() {
+        assert_eq!(
+            activity().to_json(),
+            r#"{"type":3,"details":"In \"menu\"","state":"Idle","timestamps":{"start":1700000000},"assets":{"large_image":"logo","large_text":"Logo"}}"#
+        );
+        assert_eq!(Activity::default().to_json(), r#"{"type":0}"#);
+
+        let text_only = Activity {
+            large_image_text: Some("Logo".to_string()),
+            ..Activity::default()
+        };
+        assert_eq!(text_only.to_json(), r#"{"type":0}"#);
+    }
+
+    #[test]
+    fn builds_requests() {
+        assert_eq!(handshake(42), r#"{"v":1,"client_id":"42"}"#);
+        assert_eq!(
+            set_activity(7, None, 3),
+            r#"{"cmd":"SET_ACTIVITY","args":{"pid":7},"nonce":"3"}"#
+        );
+        let request = json::parse_object(&set_activity(7, Some(&activity()), 4)).unwrap();
+        let args = request.object("args").unwrap();
+        assert_eq!(args.u64("pid"), Some(7));
+        assert_eq!(args.object("activity").unwrap().u64("type"), Some(3));
+    }
+
+    #[test]
+    fn frames_carry_opcode_and_length() {
+        assert_eq!(
+            encode_frame(OP_FRAME, "{}"),
+            [1, 0, 0, 0, 2, 0, 0, 0, b'{', b'}']
+        );
+    }
+
+    #[test]
+    fn reads_errors_from_replies() {
+        assert_eq!(
+            reply_error(
+                OP_FRAME,
+                r#"{"cmd":"DISPATCH","evt":"READY","data":{"v":1}}"#
+            ),
+            None
+        );
+        assert_eq!(
+            reply_error(
+                OP_FRAME,
+                r#"{"cmd":"SET_ACTIVITY","evt":"ERROR","data":{"code":4000,"message":"Bad activity"}}"#
+            ),
+            Some("Bad activity".to_string())
+        );
+        assert_eq!(
+            reply_error(OP_CLOSE, r#"{"code":4000,"message":"Invalid Client ID"}"#),
+            Some("Discord closed the connection: Invalid Client ID".to_string())
+        );
+    }
+}
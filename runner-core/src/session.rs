@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Tracks how long the runner has been pretending to be the game.
+#[derive(Debug, Clone, Copy)]
+pub struct Session {
+    started: Instant,
+    /// `None` runs until something else stops the runner.
+    duration: Option<Duration>,
+}
+
+impl Session {
+    pub fn start(duration: Option<Duration>) -> Self {
+        Session::started_at(Instant::now(), duration)
+    }
+
+    pub fn started_at(started: Instant, duration: Option<Duration>) -> Self {
+        Session { started, duration }
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    pub fn elapsed_at(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+    }
+
+    /// Time left until the session is over; `None` when it has no duration.
+    pub fn remaining_at(&self, now: Instant) -> Option<Duration> {
+        self.duration
+            .map(|duration| duration.saturating_sub(self.elapsed_at(now)))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_over_at(Instant::now())
+    }
+
+    pub fn is_over_at(&self, now: Instant) -> bool {
+        self.remaining_at(now)
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+}
+
+/// Why the runner is shutting down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// "Quit" from the tray menu, or Escape in variants that support it.
+    UserQuit,
+    /// The window was closed.
+    WindowClosed,
+    /// Terminated by the OS or the app (SIGTERM, SIGINT, ...).
+    Signal,
+    /// `--duration` elapsed.
+    DurationElapsed,
+    /// The process from `--parent-pid` is gone.
+    ParentExited,
+    /// The app sent `shutdown` over the control socket.
+    ControlShutdown,
+}
+
+impl ExitReason {
+    /// Name written to the status file.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExitReason::UserQuit => "user_quit",
+            ExitReason::WindowClosed => "window_closed",
+            ExitReason::Signal => "signal",
+            ExitReason::DurationElapsed => "duration_elapsed",
+            ExitReason::ParentExited => "parent_exited",
+            ExitReason::ControlShutdown => "control_shutdown",
+        }
+    }
+}
+
+/// Shared shutdown request. The first reason wins, so the runner reports what actually
+/// ended the session rather than whatever noticed it last.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    reason: Arc<Mutex<Option<ExitReason>>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Shutdown::default()
+    }
+
+    /// Requests a shutdown. Returns `false` when one was already requested.
+    pub fn request(&self, reason: ExitReason) -> bool {
+        let mut current = self.reason.lock().unwrap();
+        if current.is_some() {
+            return false;
+        }
+        *current = Some(reason);
+        true
+    }
+
+    pub fn reason(&self) -> Option<ExitReason> {
+        *self.reason.lock().unwrap()
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.reason().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_session_is_never_over() {
+        let start = Instant::now();
+        let session = Session::started_at(start, None);
+        let later = start + Duration::from_secs(24 * 60 * 60);
+        assert_eq!(session.remaining_at(later), None);
+        assert!(!session.is_over_at(later));
+        assert_eq!(session.elapsed_at(later), Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[test]
+    fn timed_session_ends_after_its_duration() {
+        let start = Instant::now();
+        let session = Session::started_at(start, Some(Duration::from_secs(60)));
+
+        let halfway = start + Duration::from_secs(30);
+        assert_eq!(session.remaining_at(halfway), Some(Duration::from_secs(30)));
+        assert!(!session.is_over_at(halfway));
+
+        let end = start + Duration::from_secs(60);
+        assert_eq!(session.remaining_at(end), Some(Duration::ZERO));
+        assert!(session.is_over_at(end));
+        assert!(session.is_over_at(end + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn elapsed_never_goes_negative() {
+        let start = Instant::now() + Duration::from_secs(10);
+        let session = Session::started_at(start, Some(Duration::from_secs(1)));
+        assert_eq!(session.elapsed_at(Instant::now()), Duration::ZERO);
+    }
+
+    #[test]
+    fn first_shutdown_reason_wins() {
+        let shutdown = Shutdown::new();
+        assert!(!shutdown.is_requested());
+
+        assert!(shutdown.request(ExitReason::UserQuit));
+        assert!(!shutdown.clone().request(ExitReason::WindowClosed));
+        assert_eq!(shutdown.reason(), Some(ExitReason::UserQuit));
+    }
+}
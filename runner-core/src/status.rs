@@ -0,0 +1,260 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::json;
+use crate::log;
+use crate::session::ExitReason;
+
+/// Bumped when fields are renamed or removed; new fields may be added freely.
+pub const STATUS_VERSION: u32 = 1;
+
+/// How often a running runner refreshes `heartbeat_at`. The app treats a runner whose
+/// heartbeat is a few intervals old as gone.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum State {
+    Running,
+    Exited {
+        /// `None` when the runner ended without recording why, e.g. the event loop failed.
+        reason: Option<ExitReason>,
+        runtime_secs: u64,
+    },
+}
+
+/// What the runner reports in `--status-file`. Times are seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+    pub pid: u32,
+    pub app_id: Option<u64>,
+    pub title: String,
+    pub started_at: u64,
+    /// Last time the runner wrote the file; the exit time once it has exited.
+    pub heartbeat_at: u64,
+    pub state: State,
+}
+
+/// Where the app keeps the status of the runner installed at `executable`. Dot files are
+/// skipped by the app's installed games listing.
+pub fn file_path(executable: &Path) -> PathBuf {
+    let file_name = executable
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    executable.with_file_name(format!(".{}.status.json", file_name))
+}
+
+/// The file [`Status::write`] writes first and renames over `path`.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    PathBuf::from(temp)
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+impl Status {
+    /// A running status for this process, started now.
+    pub fn running(app_id: Option<u64>, title: &str) -> Self {
+        let now = unix_now();
+        Status {
+            pid: std::process::id(),
+            app_id,
+            title: title.to_string(),
+            started_at: now,
+            heartbeat_at: now,
+            state: State::Running,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = format!(
+            "{{\"version\":{},\"pid\":{},\"app_id\":{},\"title\":{},\"started_at\":{},\"heartbeat_at\":{}",
+            STATUS_VERSION,
+            self.pid,
+            json::optional(self.app_id),
+            json::string(&self.title),
+            self.started_at,
+            self.heartbeat_at,
+        );
+        match &self.state {
+            State::Running => out.push_str(",\"state\":\"running\""),
+            State::Exited {
+                reason,
+                runtime_secs,
+            } => out.push_str(&format!(
+                ",\"state\":\"exited\",\"reason\":{},\"runtime_secs\":{},\"exited_at\":{}",
+                json::optional(reason.map(|reason| json::string(reason.as_str()))),
+                runtime_secs,
+                self.heartbeat_at,
+            )),
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Writes the status next to `path` first and renames it into place, so the app
+    /// never reads half a file.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let temp = temp_path(path);
+        std::fs::write(&temp, self.to_json())?;
+        std::fs::rename(&temp, path)
+    }
+}
+
+/// Keeps `--status-file` current: rewritten with a fresh heartbeat every interval while
+/// the runner runs, then once more with the exit reason by [`StatusWriter::finish`].
+#[derive(Debug)]
+pub struct StatusWriter {
+    path: PathBuf,
+    /// `None` once finished, which also stops the heartbeat thread.
+    status: Arc<Mutex<Option<Status>>>,
+}
+
+impl StatusWriter {
+    pub fn start(path: &Path, status: Status, interval: Duration) -> StatusWriter {
+        if let Err(e) = status.write(path) {
+            log!("Failed to write status file {:?}: {}", path, e);
+        }
+        let writer = StatusWriter {
+            path: path.to_path_buf(),
+            status: Arc::new(Mutex::new(Some(status))),
+        };
+
+        let (path, shared) = (writer.path.clone(), Arc::clone(&writer.status));
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            // Held while writing so `finish` can't be overwritten by a late heartbeat.
+            let mut status = shared.lock().unwrap();
+            let Some(status) = status.as_mut() else {
+                return;
+            };
+            status.heartbeat_at = unix_now();
+            if let Err(e) = status.write(&path) {
+                log!("Failed to write status file {:?}: {}", path, e);
+            }
+        });
+
+        writer
+    }
+
+    pub fn finish(self, reason: Option<ExitReason>, runtime: Duration) {
+        let Some(mut status) = self.status.lock().unwrap().take() else {
+            return;
+        };
+        status.heartbeat_at = unix_now();
+        status.state = State::Exited {
+            reason,
+            runtime_secs: runtime.as_secs(),
+        };
+        if let Err(e) = status.write(&self.path) {
+            log!("Failed to write status file {:?}: {}", self.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(state: State) -> Status {
+        Status {
+            pid: 42,
+            app_id: Some(1234),
+            title: "Some Game".to_string(),
+            started_at: 1_700_000_000,
+            heartbeat_at: 1_700_000_900,
+            state,
+        }
+    }
+
+    fn scratch_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "runner-core-{}-{}.status.json",
+            std::process::id(),
+            name
+        ))
+    }
+
+    #[test]
+    fn names_files_after_the_executable() {
+        let path = file_path(Path::new("/games/1/bin/Game.exe"));
+        assert_eq!(path, Path::new("/games/1/bin/.Game.exe.status.json"));
+        assert_eq!(
+            temp_path(&path),
+            Path::new("/games/1/bin/.Game.exe.status.json.tmp")
+        );
+    }
+
+    #[test]
+    fn serializes_a_running_runner() {
+        assert_eq!(
+            status(State::Running).to_json(),
+            "{\"version\":1,\"pid\":42,\"app_id\":1234,\"title\":\"Some Game\",\"started_at\":1700000000,\"heartbeat_at\":1700000900,\"state\":\"running\"}\n"
+        );
+    }
+
+    #[test]
+    fn serializes_the_exit_reason() {
+        let exited = status(State::Exited {
+            reason: Some(ExitReason::DurationElapsed),
+            runtime_secs: 900,
+        });
+        assert_eq!(
+            exited.to_json(),
+            "{\"version\":1,\"pid\":42,\"app_id\":1234,\"title\":\"Some Game\",\"started_at\":1700000000,\"heartbeat_at\":1700000900,\"state\":\"exited\",\"reason\":\"duration_elapsed\",\"runtime_secs\":900,\"exited_at\":1700000900}\n"
+        );
+
+        let unknown = Status {
+            app_id: None,
+            ..status(State::Exited {
+                reason: None,
+                runtime_secs: 0,
+            })
+        };
+        let json = unknown.to_json();
+        assert!(json.contains("\"reason\":null"));
+        assert!(json.contains("\"app_id\":null"));
+    }
+
+    #[test]
+    fn replaces_the_previous_status() {
+        let path = scratch_path("replace");
+        let mut status = status(State::Running);
+        status.write(&path).unwrap();
+        status.title = "Second".to_string();
+        status.write(&path).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.contains("\"title\":\"Second\""));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn heartbeats_until_finished() {
+        let path = scratch_path("heartbeat");
+        let mut running = Status::running(Some(1), "Game");
+        running.heartbeat_at = 0;
+        let writer = StatusWriter::start(&path, running, Duration::from_millis(10));
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("\"heartbeat_at\":0,"));
+
+        std::thread::sleep(Duration::from_millis(100));
+        let beating = std::fs::read_to_string(&path).unwrap();
+        assert!(beating.contains("\"state\":\"running\""));
+        assert!(!beating.contains("\"heartbeat_at\":0,"));
+
+        writer.finish(Some(ExitReason::Signal), Duration::from_secs(3));
+        std::thread::sleep(Duration::from_millis(50));
+        let exited = std::fs::read_to_string(&path).unwrap();
+        assert!(exited.contains("\"state\":\"exited\",\"reason\":\"signal\",\"runtime_secs\":3"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+use std::time::{Duration, Instant};
+
+use crate::log;
+use crate::session::{ExitReason, Session, Shutdown};
+
+/// How often the parent process is checked. The watchdog sleeps in between, so a
+/// runner without a parent to watch wakes only when its duration is up.
+pub const PARENT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Decides whether the runner should exit on its own. `parent_alive` is `None` when no
+/// parent is watched.
+pub fn check(session: &Session, parent_alive: Option<bool>, now: Instant) -> Option<ExitReason> {
+    if session.is_over_at(now) {
+        return Some(ExitReason::DurationElapsed);
+    }
+    if parent_alive == Some(false) {
+        return Some(ExitReason::ParentExited);
+    }
+    None
+}
+
+/// How long to sleep before the next check.
+fn next_check(session: &Session, watches_parent: bool, now: Instant) -> Option<Duration> {
+    let remaining = session.remaining_at(now);
+    match (remaining, watches_parent) {
+        (Some(remaining), true) => Some(remaining.min(PARENT_POLL_INTERVAL)),
+        (Some(remaining), false) => Some(remaining),
+        (None, true) => Some(PARENT_POLL_INTERVAL),
+        (None, false) => None,
+    }
+}
+
+/// Ends the session once `--duration` elapses or the `--parent-pid` process is gone.
+/// Records the reason on `shutdown` and calls `wake` so the event loop notices.
+/// Does nothing when there is neither a duration nor a parent to watch.
+pub fn spawn(
+    session: Session,
+    parent_pid: Option<u32>,
+    is_alive: fn(u32) -> bool,
+    shutdown: Shutdown,
+    wake: impl FnOnce() + Send + 'static,
+) {
+    if next_check(&session, parent_pid.is_some(), Instant::now()).is_none() {
+        return;
+    }
+
+    std::thread::spawn(move || loop {
+        if shutdown.is_requested() {
+            return;
+        }
+
+        let now = Instant::now();
+        let parent_alive = parent_pid.map(is_alive);
+        if let Some(reason) = check(&session, parent_alive, now) {
+            if shutdown.request(reason) {
+                match reason {
+                    ExitReason::ParentExited => {
+                        log!(
+                            "Parent process {} is gone, exiting",
+                            parent_pid.unwrap_or(0)
+                        )
+                    }
+                    _ => log!("Ran for {:?}, exiting", session.elapsed_at(now)),
+                }
+                wake();
+            }
+            return;
+        }
+
+        match next_check(&session, parent_pid.is_some(), now) {
+            Some(delay) => std::thread::sleep(delay),
+            None => return,
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn keeps_running_while_parent_lives_and_time_remains() {
+        let start = Instant::now();
+        let session = Session::started_at(start, Some(Duration::from_secs(10)));
+        assert_eq!(check(&session, Some(true), start), None);
+        assert_eq!(check(&session, None, start), None);
+    }
+
+    #[test]
+    fn exits_when_duration_elapses() {
+        let start = Instant::now();
+        let session = Session::started_at(start, Some(Duration::from_secs(10)));
+        let end = start + Duration::from_secs(10);
+        assert_eq!(
+            check(&session, Some(true), end),
+            Some(ExitReason::DurationElapsed)
+        );
+    }
+
+    #[test]
+    fn exits_when_parent_is_gone() {
+        let start = Instant::now();
+        let session = Session::started_at(start, None);
+        assert_eq!(
+            check(&session, Some(false), start),
+            Some(ExitReason::ParentExited)
+        );
+    }
+
+    #[test]
+    fn sleeps_until_the_next_deadline() {
+        let start = Instant::now();
+        let short = Session::started_at(start, Some(Duration::from_millis(200)));
+        assert_eq!(
+            next_check(&short, true, start),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            next_check(&short, false, start),
+            Some(Duration::from_millis(200))
+        );
+
+        let unlimited = Session::started_at(start, None);
+        assert_eq!(
+            next_check(&unlimited, true, start),
+            Some(PARENT_POLL_INTERVAL)
+        );
+        assert_eq!(next_check(&unlimited, false, start), None);
+    }
+
+    #[test]
+    fn wakes_the_runner_when_time_is_up() {
+        let shutdown = Shutdown::new();
+        let (sender, receiver) = mpsc::channel();
+        let session = Session::start(Some(Duration::from_millis(20)));
+
+        spawn(
+            session,
+            None,
+            |_| true,
+            shutdown.clone(),
+            move || sender.send(()).unwrap(),
+        );
+
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(shutdown.reason(), Some(ExitReason::DurationElapsed));
+    }
+
+    #[test]
+    fn wakes_the_runner_when_parent_exits() {
+        let shutdown = Shutdown::new();
+        let (sender, receiver) = mpsc::channel();
+
+        spawn(
+            Session::start(None),
+            Some(1234),
+            |_| false,
+            shutdown.clone(),
+            move || sender.send(()).unwrap(),
+        );
+
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(shutdown.reason(), Some(ExitReason::ParentExited));
+    }
+
+    #[test]
+    fn keeps_an_earlier_reason() {
+        let shutdown = Shutdown::new();
+        shutdown.request(ExitReason::UserQuit);
+        let session = Session::start(Some(Duration::from_millis(1)));
+
+        spawn(
+            session,
+            None,
+            |_| true,
+            shutdown.clone(),
+            || panic!("should not wake after a shutdown was requested"),
+        );
+
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(shutdown.reason(), Some(ExitReason::UserQuit));
+    }
+}
@@ -0,0 +1,17 @@
+use runner_core::{RunnerFiles, Session, Shutdown};
+use src_linux::signals;
+
+fn main() {
+    signals::block();
+    let config = src_linux::config_from_env_or_exit();
+    let files = RunnerFiles::create(&config);
+    let session = Session::start(config.duration);
+    let shutdown = Shutdown::new();
+    let presence = runner_core::presence::start(&config);
+
+    src_linux::run_headless(&config, session, &shutdown);
+    if let Some(presence) = presence {
+        presence.clear();
+    }
+    files.finish(shutdown.reason(), &session);
+}
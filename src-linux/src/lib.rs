@@ -0,0 +1,116 @@
+//! Linux runner: stands in for a game's executable so Discord sees it running.
+//!
+//! `src-linux` shows a small X11 window, `src-linux-headless` never opens one. Both block
+//! in the kernel while waiting, so they use no CPU. Neither has a tray icon, so both
+//! refuse `--tray`.
+
+use runner_core::control::{self, ControlSocket};
+use runner_core::{log, watchdog, Config, Controls, ExitReason, Session, Shutdown};
+
+pub mod signals;
+#[cfg(feature = "x11")]
+pub mod window;
+
+/// Rejects flags the shared CLI accepts but Linux runners can't honour.
+pub fn check_config(config: &Config) -> Result<(), String> {
+    if config.start_minimized {
+        // A hidden window with no tray icon would leave nothing to show or quit it from.
+        return Err(
+            "--tray is not supported on Linux, there is no tray icon to bring the runner back"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Parses the process arguments like [`runner_core::from_env_or_exit`], and also exits
+/// with status 2 when [`check_config`] refuses them.
+pub fn config_from_env_or_exit() -> Config {
+    let config = runner_core::from_env_or_exit(Config::default());
+    if let Err(e) = check_config(&config) {
+        eprintln!("Error: {}\n\nRun with --help for usage.", e);
+        std::process::exit(2);
+    }
+    config
+}
+
+/// Whether the process `pid` still exists. EPERM means it exists but belongs to
+/// someone else.
+pub fn is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Ends the session on `--duration` or when `--parent-pid` exits, then calls `wake`.
+pub fn spawn_watchdog(
+    config: &Config,
+    session: Session,
+    shutdown: &Shutdown,
+    wake: impl FnOnce() + Send + 'static,
+) {
+    watchdog::spawn(session, config.parent_pid, is_alive, shutdown.clone(), wake);
+}
+
+/// Answers requests on `--control-socket`, if given, until the returned guard is dropped.
+pub fn serve_control(config: &Config, controls: Controls) -> Option<ControlSocket> {
+    let path = config.control_socket.as_deref()?;
+    match control::serve(path, controls) {
+        Ok(socket) => {
+            log!("Listening for control requests on {:?}", path);
+            Some(socket)
+        }
+        Err(e) => {
+            log!("Failed to open control socket {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Wakes the `sigwait` in [`run_headless`] by sending ourselves SIGTERM.
+fn wake_headless() {
+    unsafe {
+        libc::kill(libc::getpid(), libc::SIGTERM);
+    }
+}
+
+/// Stays resident without a window until a shutdown signal arrives, the watchdog ends
+/// the session or the app asks over the control socket.
+pub fn run_headless(config: &Config, session: Session, shutdown: &Shutdown) {
+    log!("Running {:?} without a window", config.title);
+    spawn_watchdog(config, session, shutdown, wake_headless);
+    let controls = Controls::new(config, session, shutdown.clone()).wake(wake_headless);
+    let _control = serve_control(config, controls);
+
+    let signal = signals::wait();
+    if shutdown.request(ExitReason::Signal) {
+        log!("Received signal {}, exiting", signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_tray() {
+        assert_eq!(check_config(&Config::default()), Ok(()));
+        let tray = Config {
+            start_minimized: true,
+            ..Config::default()
+        };
+        assert!(check_config(&tray).unwrap_err().contains("--tray"));
+    }
+
+    #[test]
+    fn tells_live_processes() {
+        assert!(is_alive(std::process::id()));
+        // pid 1 belongs to root; EPERM still means it exists.
+        assert!(is_alive(1));
+        assert!(!is_alive(u32::MAX));
+    }
+}
@@ -0,0 +1,32 @@
+use runner_core::{Config, RunnerFiles, Session, Shutdown};
+use src_linux::signals;
+
+fn main() {
+    signals::block();
+    let config = src_linux::config_from_env_or_exit();
+    let files = RunnerFiles::create(&config);
+    let session = Session::start(config.duration);
+    let shutdown = Shutdown::new();
+    let presence = runner_core::presence::start(&config);
+
+    run(&config, session, &shutdown);
+    if let Some(presence) = presence {
+        presence.clear();
+    }
+    files.finish(shutdown.reason(), &session);
+}
+
+fn run(config: &Config, session: Session, shutdown: &Shutdown) {
+    #[cfg(feature = "x11")]
+    match src_linux::window::Window::open(config) {
+        Ok(window) => {
+            let lifecycle = runner_core::Lifecycle::new(config, shutdown.clone());
+            window.run(config, &lifecycle, session, shutdown.clone());
+            return;
+        }
+        // No X server (SSH session, bare TTY, ...); the process alone is enough for Discord
+        Err(e) => runner_core::log!("Failed to open a window, running headless: {}", e),
+    }
+
+    src_linux::run_headless(config, session, shutdown);
+}
@@ -0,0 +1,48 @@
+use runner_core::{log, ExitReason, Shutdown};
+
+/// Signals that end the runner. The app stops runners with SIGTERM.
+const SHUTDOWN_SIGNALS: [libc::c_int; 3] = [libc::SIGTERM, libc::SIGINT, libc::SIGHUP];
+
+fn shutdown_set() -> libc::sigset_t {
+    unsafe {
+        let mut set: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        for signal in SHUTDOWN_SIGNALS {
+            libc::sigaddset(&mut set, signal);
+        }
+        set
+    }
+}
+
+/// Blocks the shutdown signals so they queue up for [`wait`] instead of killing the
+/// process. Call it first thing in `main`, threads spawned later inherit the mask.
+pub fn block() {
+    let set = shutdown_set();
+    let result = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut()) };
+    if result != 0 {
+        log!("Failed to block shutdown signals: {}", result);
+    }
+}
+
+/// Sleeps until a shutdown signal arrives and returns it.
+pub fn wait() -> libc::c_int {
+    let set = shutdown_set();
+    let mut signal = 0;
+    loop {
+        if unsafe { libc::sigwait(&set, &mut signal) } == 0 {
+            return signal;
+        }
+    }
+}
+
+/// Waits for a shutdown signal on a background thread, records it and runs `on_signal`
+/// so the event loop can be woken up.
+pub fn spawn_watcher(shutdown: Shutdown, on_signal: impl FnOnce() + Send + 'static) {
+    std::thread::spawn(move || {
+        let signal = wait();
+        if shutdown.request(ExitReason::Signal) {
+            log!("Received signal {}, exiting", signal);
+        }
+        on_signal();
+    });
+}
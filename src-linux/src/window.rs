@@ -0,0 +1,225 @@
+use runner_core::{log, Action, Config, Controls, ExitReason, Lifecycle, Session, Shutdown};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use x11rb::connection::Connection;
+use x11rb::errors::ConnectionError;
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConnectionExt as _, CreateGCAux, CreateWindowAux, EventMask,
+    Gcontext, PropMode, Window as WindowId, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+use crate::signals;
+
+const WIDTH: u16 = 400;
+const HEIGHT: u16 = 400;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        WM_PROTOCOLS,
+        WM_DELETE_WINDOW,
+        UTF8_STRING,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        // Sent to ourselves to wake the event loop when a signal arrives or the
+        // watchdog ends the session.
+        WAKE: b"_DQC_RUNNER_WAKE",
+    }
+}
+
+/// A plain X11 window titled after the game. Also works under XWayland.
+pub struct Window {
+    conn: Arc<RustConnection>,
+    id: WindowId,
+    gc: Gcontext,
+    atoms: Atoms,
+    /// Changed from the control socket's thread by `rename_title`.
+    title: Arc<Mutex<String>>,
+}
+
+/// Core X fonts only cover Latin-1; anything else is drawn as `?`.
+fn latin1(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .take(255)
+        .collect()
+}
+
+fn write_title(
+    conn: &RustConnection,
+    id: WindowId,
+    atoms: &Atoms,
+    title: &str,
+) -> Result<(), ConnectionError> {
+    conn.change_property8(
+        PropMode::REPLACE,
+        id,
+        AtomEnum::WM_NAME,
+        AtomEnum::STRING,
+        &latin1(title),
+    )?;
+    conn.change_property8(
+        PropMode::REPLACE,
+        id,
+        atoms._NET_WM_NAME,
+        atoms.UTF8_STRING,
+        title.as_bytes(),
+    )?;
+    Ok(())
+}
+
+impl Window {
+    pub fn open(config: &Config) -> Result<Window, Box<dyn Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let screen = &conn.setup().roots[screen_num];
+        let atoms = Atoms::new(&conn)?.reply()?;
+
+        let id = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            id,
+            screen.root,
+            0,
+            0,
+            WIDTH,
+            HEIGHT,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new()
+                .background_pixel(screen.white_pixel)
+                .event_mask(EventMask::EXPOSURE),
+        )?;
+
+        write_title(&conn, id, &atoms, &config.title)?;
+        conn.change_property8(
+            PropMode::REPLACE,
+            id,
+            AtomEnum::WM_CLASS,
+            AtomEnum::STRING,
+            b"src-linux\0DiscordQuestCompleter\0",
+        )?;
+        conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            atoms.WM_PROTOCOLS,
+            AtomEnum::ATOM,
+            &[atoms.WM_DELETE_WINDOW],
+        )?;
+        conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            atoms._NET_WM_PID,
+            AtomEnum::CARDINAL,
+            &[std::process::id()],
+        )?;
+
+        let font = conn.generate_id()?;
+        conn.open_font(font, b"fixed")?;
+        let gc = conn.generate_id()?;
+        conn.create_gc(
+            gc,
+            id,
+            &CreateGCAux::new()
+                .foreground(screen.black_pixel)
+                .background(screen.white_pixel)
+                .font(font),
+        )?;
+        conn.close_font(font)?;
+        conn.flush()?;
+
+        Ok(Window {
+            conn: Arc::new(conn),
+            id,
+            gc,
+            atoms,
+            title: Arc::new(Mutex::new(config.title.clone())),
+        })
+    }
+
+    fn draw(&self) -> Result<(), Box<dyn Error>> {
+        let title = self.title.lock().unwrap().clone();
+        let lines = [
+            (20, latin1("Discord Quest Completer")),
+            (50, latin1(&title)),
+            (
+                70,
+                latin1("This program is part of the Discord Quest Completer"),
+            ),
+        ];
+        for (y, text) in lines {
+            self.conn.image_text8(self.id, self.gc, 10, y, &text)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Runs the event loop until the window is closed, a shutdown signal arrives or the
+    /// watchdog ends the session. Blocks on the X connection in between, so an idle
+    /// runner uses no CPU.
+    pub fn run(self, config: &Config, lifecycle: &Lifecycle, session: Session, shutdown: Shutdown) {
+        let conn = Arc::clone(&self.conn);
+        let (id, atom) = (self.id, self.atoms.WAKE);
+        let wake = move || {
+            let event = ClientMessageEvent::new(32, id, atom, [0u32; 5]);
+            let _ = conn.send_event(false, id, EventMask::NO_EVENT, event);
+            let _ = conn.flush();
+        };
+        signals::spawn_watcher(shutdown.clone(), wake.clone());
+        crate::spawn_watchdog(config, session, &shutdown, wake.clone());
+
+        let (conn, title, atoms) = (Arc::clone(&self.conn), Arc::clone(&self.title), self.atoms);
+        let controls = Controls::new(config, session, shutdown.clone())
+            .wake(wake)
+            .on_rename(move |new_title| {
+                *title.lock().unwrap() = new_title.to_string();
+                // Clearing the whole window triggers an Expose, which redraws the title.
+                let result = write_title(&conn, id, &atoms, new_title)
+                    .and_then(|_| conn.clear_area(true, id, 0, 0, 0, 0).map(|_| ()))
+                    .and_then(|_| conn.flush());
+                if let Err(e) = result {
+                    log!("Failed to rename the window: {}", e);
+                }
+            });
+        let _control = crate::serve_control(config, controls);
+
+        // Always shown: `--tray` is refused at startup, see `check_config`.
+        if lifecycle.initial_action() == Action::Show {
+            let _ = self.conn.map_window(self.id);
+            let _ = self.conn.flush();
+        }
+
+        loop {
+            match self.conn.wait_for_event() {
+                Ok(Event::Expose(event)) if event.count == 0 => {
+                    if let Err(e) = self.draw() {
+                        log!("Failed to draw the window: {}", e);
+                    }
+                }
+                Ok(Event::ClientMessage(event)) => {
+                    let data = event.data.as_data32();
+                    if event.type_ == self.atoms.WM_PROTOCOLS
+                        && data[0] == self.atoms.WM_DELETE_WINDOW
+                    {
+                        shutdown.request(ExitReason::WindowClosed);
+                        break;
+                    }
+                    if event.type_ == self.atoms.WAKE && shutdown.is_requested() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    log!("Lost the connection to the X server: {}", e);
+                    break;
+                }
+            }
+        }
+
+        let _ = self.conn.destroy_window(self.id);
+        let _ = self.conn.flush();
+    }
+}
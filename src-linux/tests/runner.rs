@@ -0,0 +1,194 @@
+//! Runs the runners the way the app does and checks what they leave behind. The headless
+//! one is used wherever both behave the same, since it needs no display.
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+use runner_core::json;
+
+const RUNNER: &str = env!("CARGO_BIN_EXE_src-linux-headless");
+const WINDOW_RUNNER: &str = env!("CARGO_BIN_EXE_src-linux");
+
+/// A scratch folder removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("src-linux-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(RUNNER).args(args).output().unwrap()
+}
+
+fn spawn(args: &[&str]) -> Child {
+    Command::new(RUNNER)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap()
+}
+
+/// Waits up to `timeout` for `child` to exit and returns whether it exited cleanly.
+fn wait(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return status.success();
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            panic!("runner didn't exit within {:?}", timeout);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn wait_for(path: &Path) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !path.exists() {
+        assert!(Instant::now() < deadline, "{:?} never appeared", path);
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn read_status(path: &Path) -> json::Object {
+    json::parse_object(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn prints_help() {
+    let output = run(&["--help"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("--title"));
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    for args in [&["--bogus"][..], &["--duration", "0"], &["--title"]] {
+        let output = run(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Error:"));
+    }
+}
+
+#[test]
+fn rejects_tray() {
+    for runner in [RUNNER, WINDOW_RUNNER] {
+        let output = Command::new(runner).arg("--tray").output().unwrap();
+        assert_eq!(output.status.code(), Some(2), "{}", runner);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--tray is not supported"));
+    }
+}
+
+#[test]
+fn ends_its_own_session() {
+    let dir = TempDir::new("duration");
+    let status = dir.join("status.json");
+    // Without --presence the app id only goes into the status file.
+    let mut child = spawn(&[
+        "--duration",
+        "1",
+        "--app-id",
+        "42",
+        "--status-file",
+        status.to_str().unwrap(),
+    ]);
+    assert!(wait(&mut child, Duration::from_secs(10)));
+
+    let status = read_status(&status);
+    assert_eq!(status.str("state"), Some("exited"));
+    assert_eq!(status.str("reason"), Some("duration_elapsed"));
+    assert_eq!(status.u64("app_id"), Some(42));
+}
+
+#[test]
+fn exits_with_its_parent() {
+    let dir = TempDir::new("parent");
+    let status = dir.join("status.json");
+    let mut parent = Command::new("sleep").arg("30").spawn().unwrap();
+    let mut child = spawn(&[
+        "--parent-pid",
+        &parent.id().to_string(),
+        "--status-file",
+        status.to_str().unwrap(),
+    ]);
+    wait_for(&status);
+    parent.kill().unwrap();
+    parent.wait().unwrap();
+
+    assert!(wait(&mut child, Duration::from_secs(10)));
+    assert_eq!(read_status(&status).str("reason"), Some("parent_exited"));
+}
+
+#[test]
+fn records_signals() {
+    let dir = TempDir::new("signal");
+    let status = dir.join("status.json");
+    let mut child = spawn(&["--status-file", status.to_str().unwrap()]);
+    wait_for(&status);
+    assert_eq!(read_status(&status).str("state"), Some("running"));
+
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+    }
+    assert!(wait(&mut child, Duration::from_secs(10)));
+    assert_eq!(read_status(&status).str("reason"), Some("signal"));
+}
+
+#[test]
+fn answers_on_its_control_socket() {
+    let dir = TempDir::new("control");
+    let socket = dir.join("control.sock");
+    let status = dir.join("status.json");
+    let mut child = spawn(&[
+        "--title",
+        "Game",
+        "--control-socket",
+        socket.to_str().unwrap(),
+        "--status-file",
+        status.to_str().unwrap(),
+    ]);
+    wait_for(&socket);
+
+    let request = |line: &str| {
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        writeln!(stream, "{}", line).unwrap();
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply).unwrap();
+        json::parse_object(&reply).unwrap()
+    };
+
+    let ping = request(r#"{"version":1,"command":"ping"}"#);
+    assert_eq!(ping.get("ok"), Some(&json::Value::Bool(true)));
+    let renamed = request(r#"{"version":1,"command":"rename_title","title":"Other"}"#);
+    assert_eq!(renamed.str("title"), Some("Other"));
+    let state = request(r#"{"version":1,"command":"status"}"#);
+    assert_eq!(state.str("title"), Some("Other"));
+    assert_eq!(state.u64("pid"), Some(u64::from(child.id())));
+    let refused = request(r#"{"version":2,"command":"ping"}"#);
+    assert_eq!(refused.get("ok"), Some(&json::Value::Bool(false)));
+
+    request(r#"{"version":1,"command":"shutdown"}"#);
+    assert!(wait(&mut child, Duration::from_secs(10)));
+    assert_eq!(read_status(&status).str("reason"), Some("control_shutdown"));
+    assert!(!socket.exists());
+}
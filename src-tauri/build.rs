@@ -1,3 +1,54 @@
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Runner templates bundled under `data/` (see `bundle.resources` in `tauri.conf.json`
+/// and `tauri.linux.conf.json`), as (name, file in `resources/`).
+const WINDOWS_TEMPLATES: &[(&str, &str)] = &[("windows", "src-win.exe")];
+const LINUX_TEMPLATES: &[(&str, &str)] = &[("linux", "src-linux")];
+
+fn templates() -> &'static [(&'static str, &'static str)] {
+    match std::env::var("CARGO_CFG_TARGET_OS").as_deref() {
+        Ok("linux") => LINUX_TEMPLATES,
+        _ => WINDOWS_TEMPLATES,
+    }
+}
+
+/// Records the SHA-256 of every runner template so the app can verify them before copying.
+fn write_template_manifest() {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let mut manifest = String::from("pub const TEMPLATES: &[TemplateEntry] = &[\n");
+
+    for (name, file) in templates() {
+        let source = Path::new("resources").join(file);
+        println!("cargo:rerun-if-changed={}", source.display());
+
+        let sha256 = match std::fs::read(&source) {
+            Ok(bytes) => format!("Some({:?})", format!("{:x}", Sha256::digest(&bytes))),
+            Err(e) => {
+                println!(
+                    "cargo:warning=Runner template {} not found ({}), run `pnpm sync:runner` (`sync:runner:linux` on Linux)",
+                    source.display(),
+                    e
+                );
+                "None".to_string()
+            }
+        };
+        writeln!(
+            manifest,
+            "    TemplateEntry {{ name: {:?}, resource: {:?}, sha256: {} }},",
+            name,
+            format!("data/{}", file),
+            sha256
+        )
+        .unwrap();
+    }
+    manifest.push_str("];\n");
+
+    std::fs::write(Path::new(&out_dir).join("templates.rs"), manifest).unwrap();
+}
+
 fn main() {
+    write_template_manifest();
     tauri_build::build()
 }
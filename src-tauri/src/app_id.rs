@@ -0,0 +1,63 @@
+//! App ids are Discord snowflakes, which don't fit in a JavaScript number: `Number(id)`
+//! rounds anything above 2^53. They cross the frontend boundary as strings and are `i64`
+//! everywhere else. Use `#[serde(with = "crate::app_id")]` on fields the frontend sees and
+//! [`parse`] for command arguments.
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Parses an app id sent by the frontend.
+pub fn parse(app_id: &str) -> Result<i64, String> {
+    app_id
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid app id {:?}", app_id))
+}
+
+pub fn serialize<S: Serializer>(app_id: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(app_id)
+}
+
+/// Accepts a string, or a number for callers that still send one.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(i64),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(app_id) => Ok(app_id),
+        Raw::Text(text) => parse(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Game {
+        #[serde(with = "super")]
+        app_id: i64,
+    }
+
+    #[test]
+    fn snowflakes_round_trip_as_strings() {
+        let game = Game {
+            app_id: 1158877933042143272,
+        };
+        let json = serde_json::to_string(&game).unwrap();
+        assert_eq!(json, r#"{"app_id":"1158877933042143272"}"#);
+        assert_eq!(
+            serde_json::from_str::<Game>(&json).unwrap().app_id,
+            1158877933042143272
+        );
+        assert_eq!(
+            serde_json::from_str::<Game>(r#"{"app_id":42}"#)
+                .unwrap()
+                .app_id,
+            42
+        );
+        assert!(serde_json::from_str::<Game>(r#"{"app_id":"4.2"}"#).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::install;
+
+/// Emitted once per item as soon as it is installed or has failed.
+pub const EVENT_INSTALL_PROGRESS: &str = "install_progress";
+
+/// Upper bound on concurrent installs; they are mostly disk bound.
+const MAX_WORKERS: usize = 4;
+
+/// One executable to install, as sent by the frontend.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BatchInstallRequest {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub path: String,
+    pub executable_name: String,
+    pub name: Option<String>,
+    /// The executable entry as it appears in the detectable game list.
+    pub detectable: Option<String>,
+    /// Segment count of the entry, as passed to `create_fake_game`; 0 when unknown.
+    #[serde(default)]
+    pub path_len: i64,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchInstallStatus {
+    Installed,
+    Failed,
+}
+
+/// Payload of [`EVENT_INSTALL_PROGRESS`].
+#[derive(Serialize, Clone, Debug)]
+pub struct BatchInstallProgress {
+    /// Position of the item in the request list.
+    pub index: usize,
+    pub request: BatchInstallRequest,
+    pub status: BatchInstallStatus,
+    pub path: Option<String>,
+    pub error: Option<String>,
+    /// Items finished so far, including this one.
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BatchInstallFailure {
+    pub index: usize,
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub executable_name: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct BatchInstallSummary {
+    pub total: usize,
+    /// Installed executables, in request order.
+    pub installed: Vec<String>,
+    pub failed: Vec<BatchInstallFailure>,
+}
+
+/// Installs `requests` on a small pool of worker threads and blocks until all are done.
+///
+/// Items of the same app are installed by one worker in order, so two installs never
+/// create or roll back the same game folder at the same time.
+pub fn install_all(handle: &AppHandle, requests: Vec<BatchInstallRequest>) -> BatchInstallSummary {
+    let total = requests.len();
+
+    let mut by_app: BTreeMap<i64, Vec<(usize, BatchInstallRequest)>> = BTreeMap::new();
+    for (index, request) in requests.into_iter().enumerate() {
+        by_app
+            .entry(request.app_id)
+            .or_default()
+            .push((index, request));
+    }
+    let groups: Vec<Vec<(usize, BatchInstallRequest)>> = by_app.into_values().collect();
+
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_WORKERS)
+        .min(groups.len());
+    let next_group = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let results: Mutex<Vec<BatchInstallProgress>> = Mutex::new(Vec::with_capacity(total));
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while let Some(group) = groups.get(next_group.fetch_add(1, Ordering::Relaxed)) {
+                    for (index, request) in group {
+                        let details = install::GameDetails {
+                            name: request.name.as_deref(),
+                            detectable: request.detectable.as_deref(),
+                            segments: install::segments(request.path_len),
+                        };
+                        let result = install::install(
+                            handle,
+                            &request.path,
+                            &request.executable_name,
+                            request.app_id,
+                            details,
+                        );
+
+                        let (status, path, error) = match result {
+                            Ok(path) => (
+                                BatchInstallStatus::Installed,
+                                Some(path.to_string_lossy().to_string()),
+                                None,
+                            ),
+                            Err(e) => (BatchInstallStatus::Failed, None, Some(e.to_string())),
+                        };
+                        let progress = BatchInstallProgress {
+                            index: *index,
+                            request: request.clone(),
+                            status,
+                            path,
+                            error,
+                            completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                            total,
+                        };
+                        handle
+                            .emit(EVENT_INSTALL_PROGRESS, progress.clone())
+                            .unwrap_or_else(|e| eprintln!("Failed to emit event: {}", e));
+                        results.lock().unwrap().push(progress);
+                    }
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|progress| progress.index);
+
+    let mut summary = BatchInstallSummary {
+        total,
+        ..Default::default()
+    };
+    for progress in results {
+        match (progress.path, progress.error) {
+            (Some(path), _) => summary.installed.push(path),
+            (None, error) => summary.failed.push(BatchInstallFailure {
+                index: progress.index,
+                app_id: progress.request.app_id,
+                executable_name: progress.request.executable_name,
+                error: error.unwrap_or_default(),
+            }),
+        }
+    }
+    println!(
+        "Installed {} of {} games ({} failed)",
+        summary.installed.len(),
+        total,
+        summary.failed.len()
+    );
+    summary
+}
@@ -0,0 +1,126 @@
+//! Client for the control socket runners open with `--control-socket`.
+//!
+//! One JSON object per line in each direction, see `runner-core/src/control.rs` for the
+//! runner's side. Only Unix runners have a control socket; everywhere else every request
+//! fails and callers fall back to signals.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// How long to wait for a runner to answer before giving up on it.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The runner's answer to `status`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunnerStatus {
+    pub pid: u32,
+    pub title: String,
+    /// Measured by the runner itself, from its own start.
+    pub uptime_secs: u64,
+    pub duration_secs: Option<u64>,
+    pub remaining_secs: Option<u64>,
+}
+
+/// Folder holding the control sockets. `$XDG_RUNTIME_DIR` is private to the user and
+/// short enough for the ~100 byte socket path limit.
+#[cfg(unix)]
+fn socket_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(std::env::temp_dir)
+        .join("discord-quest-completer")
+}
+
+/// A fresh socket path for the next runner, or `None` when the folder can't be created.
+#[cfg(unix)]
+pub fn socket_path() -> Option<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+    let dir = socket_dir();
+    if let Err(e) = std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+    {
+        eprintln!("Failed to create control socket folder {:?}: {}", dir, e);
+        return None;
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    Some(dir.join(format!("{}-{}.sock", std::process::id(), id)))
+}
+
+#[cfg(not(unix))]
+pub fn socket_path() -> Option<PathBuf> {
+    None
+}
+
+/// Sends one request and returns the runner's reply, or its error message.
+#[cfg(unix)]
+fn request(path: &Path, mut body: Value) -> Result<Value, String> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    body["version"] = json!(PROTOCOL_VERSION);
+    let stream = UnixStream::connect(path)
+        .map_err(|e| format!("Failed to connect to runner at {:?}: {}", path, e))?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|e| format!("Failed to configure runner socket: {}", e))?;
+
+    let mut line = body.to_string();
+    line.push('\n');
+    (&stream)
+        .write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to send request to runner: {}", e))?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| format!("Runner didn't answer: {}", e))?;
+    let reply: Value = serde_json::from_str(&reply)
+        .map_err(|e| format!("Failed to parse runner reply {:?}: {}", reply.trim(), e))?;
+
+    if reply["version"] != json!(PROTOCOL_VERSION) {
+        return Err(format!(
+            "Runner speaks protocol version {}, expected {}",
+            reply["version"], PROTOCOL_VERSION
+        ));
+    }
+    if reply["ok"] != json!(true) {
+        return Err(format!(
+            "Runner refused the request: {}",
+            reply["error"].as_str().unwrap_or("unknown error")
+        ));
+    }
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+fn request(_path: &Path, _body: Value) -> Result<Value, String> {
+    Err("Runner control sockets are only supported on Unix".to_string())
+}
+
+pub fn status(path: &Path) -> Result<RunnerStatus, String> {
+    let reply = request(path, json!({ "command": "status" }))?;
+    serde_json::from_value(reply).map_err(|e| format!("Failed to parse runner status: {}", e))
+}
+
+/// Changes the runner's window title and returns the title it now shows.
+pub fn rename_title(path: &Path, title: &str) -> Result<String, String> {
+    let reply = request(path, json!({ "command": "rename_title", "title": title }))?;
+    Ok(reply["title"].as_str().unwrap_or(title).to_string())
+}
+
+/// Asks the runner to exit. It replies before it starts shutting down.
+pub fn shutdown(path: &Path) -> Result<(), String> {
+    request(path, json!({ "command": "shutdown" })).map(|_| ())
+}
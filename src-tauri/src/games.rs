@@ -0,0 +1,757 @@
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::install::{self, InstallMethod};
+use crate::{heartbeat, manifest, process, sanitize, settings, upgrade};
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GamesRootSource {
+    /// Set explicitly through the `games_root` setting.
+    Configured,
+    /// `<exe dir>/games`, kept for portable installs that already have games there.
+    Portable,
+    /// `games` inside the platform data dir (`%APPDATA%`, `$XDG_DATA_HOME`, ...).
+    DataDir,
+    /// The configured root wasn't writable, so the data dir is used instead.
+    Fallback,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GamesRoot {
+    pub path: PathBuf,
+    pub source: GamesRootSource,
+}
+
+static GAMES_ROOT: OnceCell<Mutex<GamesRoot>> = OnceCell::new();
+
+/// Where games used to be created before the root became configurable.
+fn portable_root() -> PathBuf {
+    let exe_path: PathBuf = env::current_exe().unwrap_or_default();
+    let exe_dir = exe_path.parent().unwrap_or_else(|| Path::new(""));
+    exe_dir.join("games")
+}
+
+fn data_dir_root(handle: &AppHandle) -> Option<PathBuf> {
+    handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join("games"))
+        .map_err(|e| eprintln!("Failed to resolve app data dir: {}", e))
+        .ok()
+}
+
+/// Creates `dir` if needed and checks a file can actually be written into it, which also
+/// catches read-only installs under `Program Files` that `metadata` would report as fine.
+fn is_writable(dir: &Path) -> bool {
+    if std::fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let probe = dir.join(format!(".write-test-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(_) => {
+            let _ = std::fs::remove_file(probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+fn pick_root(handle: &AppHandle) -> GamesRoot {
+    let configured = settings::get().games_root;
+
+    if let Some(path) = &configured {
+        if is_writable(path) {
+            return GamesRoot {
+                path: path.clone(),
+                source: GamesRootSource::Configured,
+            };
+        }
+        eprintln!("Configured games root {:?} is not writable", path);
+    } else {
+        let portable = portable_root();
+        if portable.is_dir() && is_writable(&portable) {
+            return GamesRoot {
+                path: portable,
+                source: GamesRootSource::Portable,
+            };
+        }
+    }
+
+    let source = if configured.is_some() {
+        GamesRootSource::Fallback
+    } else {
+        GamesRootSource::DataDir
+    };
+    match data_dir_root(handle) {
+        Some(path) if is_writable(&path) => GamesRoot { path, source },
+        _ => GamesRoot {
+            // Nothing writable was found; keep the old behaviour so errors point at a real path.
+            path: portable_root(),
+            source: GamesRootSource::Portable,
+        },
+    }
+}
+
+/// Resolves the games root from the settings. Called at startup and whenever the setting changes.
+pub fn init_root(handle: &AppHandle) -> GamesRoot {
+    let root = pick_root(handle);
+    println!("Games root: {:?} ({:?})", root.path, root.source);
+    *GAMES_ROOT
+        .get_or_init(|| Mutex::new(root.clone()))
+        .lock()
+        .unwrap() = root.clone();
+    root
+}
+
+pub fn games_root_info() -> GamesRoot {
+    match GAMES_ROOT.get() {
+        Some(root) => root.lock().unwrap().clone(),
+        None => GamesRoot {
+            path: portable_root(),
+            source: GamesRootSource::Portable,
+        },
+    }
+}
+
+/// Root every dummy install lives under.
+pub fn games_root() -> PathBuf {
+    games_root_info().path
+}
+
+/// A game an operation left alone, and why.
+#[derive(Serialize, Clone, Debug)]
+pub struct SkippedGame {
+    pub app_id: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MigrationReport {
+    pub from: Vec<PathBuf>,
+    pub to: PathBuf,
+    pub moved: Vec<String>,
+    pub skipped: Vec<SkippedGame>,
+}
+
+fn copy_dir_all(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves a folder, copying it when `rename` can't cross filesystems.
+fn move_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = copy_dir_all(from, to) {
+        let _ = std::fs::remove_dir_all(to);
+        return Err(e);
+    }
+    std::fs::remove_dir_all(from)
+}
+
+/// Whether `name` is a folder `install` creates, i.e. an app id written the way
+/// `i64::to_string` writes it. Dot folders like `.staging` never are.
+fn is_app_folder(name: &str) -> bool {
+    !name.starts_with('.') && name.parse::<i64>().is_ok_and(|id| id.to_string() == name)
+}
+
+/// `path` with symlinks and `..` resolved where it exists, so nesting checks aren't
+/// fooled by different spellings of the same folder.
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Moves every `games/<app_id>` tree from the portable root and the current root into
+/// `target` (the data dir when `None`), then makes `target` the configured root.
+/// Games that are running or already exist in `target` are left where they are.
+pub fn migrate_root(
+    handle: &AppHandle,
+    target: Option<PathBuf>,
+) -> Result<MigrationReport, String> {
+    let target = match target {
+        Some(target) => target,
+        None => data_dir_root(handle).ok_or("Failed to resolve app data dir")?,
+    };
+    if !target.is_absolute() {
+        return Err(format!("Games root {:?} must be an absolute path", target));
+    }
+    if !is_writable(&target) {
+        return Err(format!("Games root {:?} is not writable", target));
+    }
+
+    let mut from = vec![portable_root(), games_root()];
+    from.dedup();
+    from.retain(|source| source != &target && source.is_dir());
+
+    // Copying a root into a folder inside itself would never finish, and moving a root
+    // into one it contains would move the target along with it.
+    let normalized_target = normalize(&target);
+    for source in &from {
+        let source = normalize(source);
+        if normalized_target.starts_with(&source) || source.starts_with(&normalized_target) {
+            return Err(format!(
+                "Games root {:?} can't be inside {:?} or contain it",
+                target, source
+            ));
+        }
+    }
+
+    let running: Vec<String> = process::list()
+        .iter()
+        .map(|process| process.app_id.to_string())
+        .collect();
+
+    let mut moved = Vec::new();
+    let mut skipped = Vec::new();
+    for source in &from {
+        let entries =
+            std::fs::read_dir(source).map_err(|e| format!("Failed to read {:?}: {}", source, e))?;
+        for entry in entries.flatten() {
+            let app_id = entry.file_name().to_string_lossy().to_string();
+            // The root may be a shared folder; only touch what `install` put there.
+            if !entry.file_type().is_ok_and(|kind| kind.is_dir()) || !is_app_folder(&app_id) {
+                continue;
+            }
+            let destination = target.join(&app_id);
+
+            let reason = if running.contains(&app_id) {
+                Some("a runner for this game is running".to_string())
+            } else if destination.exists() {
+                Some(format!("{:?} already exists", destination))
+            } else {
+                move_dir(&entry.path(), &destination)
+                    .err()
+                    .map(|e| format!("Failed to move: {}", e))
+            };
+
+            match reason {
+                Some(reason) => skipped.push(SkippedGame { app_id, reason }),
+                None => moved.push(app_id),
+            }
+        }
+        // Only succeeds once the old root is empty, which leftover staging folders or
+        // unrelated files prevent.
+        let _ = std::fs::remove_dir(source);
+    }
+
+    settings::update(|settings| settings.games_root = Some(target.clone()))?;
+    init_root(handle);
+
+    Ok(MigrationReport {
+        from,
+        to: target,
+        moved,
+        skipped,
+    })
+}
+
+/// Where a game's dummy executable goes, after sanitising the values from the game list.
+pub struct GamePaths {
+    pub app_id: i64,
+    /// `games/<app_id>/<path>`
+    pub folder: PathBuf,
+    /// `games/<app_id>/<path>/<executable_name>`
+    pub executable: PathBuf,
+    pub sanitized: sanitize::SanitizedPath,
+}
+
+impl GamePaths {
+    /// File name of [`GamePaths::executable`].
+    pub fn file_name(&self) -> String {
+        self.executable
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn process_key(&self) -> process::ProcessKey {
+        process::ProcessKey::new(self.app_id, &self.file_name())
+    }
+}
+
+/// Paths of a game list entry under the games root. Changing the path case setting
+/// doesn't move existing installs, so an executable already on disk under the other case
+/// is used as is; otherwise the paths follow the current setting.
+pub fn resolve(
+    app_id: i64,
+    path: &str,
+    executable_name: &str,
+) -> Result<GamePaths, sanitize::SanitizeError> {
+    let case = settings::get().path_case;
+    resolve_in(&games_root(), app_id, path, executable_name, case)
+}
+
+fn resolve_in(
+    root: &Path,
+    app_id: i64,
+    path: &str,
+    executable_name: &str,
+    case: sanitize::PathCase,
+) -> Result<GamePaths, sanitize::SanitizeError> {
+    let paths = resolve_with_case(root, app_id, path, executable_name, case)?;
+    if paths.executable.is_file() {
+        return Ok(paths);
+    }
+    let other = match case {
+        sanitize::PathCase::Preserve => sanitize::PathCase::Lowercase,
+        sanitize::PathCase::Lowercase => sanitize::PathCase::Preserve,
+    };
+    match resolve_with_case(root, app_id, path, executable_name, other) {
+        Ok(existing) if existing.executable.is_file() => Ok(existing),
+        _ => Ok(paths),
+    }
+}
+
+fn resolve_with_case(
+    root: &Path,
+    app_id: i64,
+    path: &str,
+    executable_name: &str,
+    case: sanitize::PathCase,
+) -> Result<GamePaths, sanitize::SanitizeError> {
+    let sanitized = sanitize::sanitize_dir(path)?.with_case(case);
+    let file_name =
+        sanitize::file_name_with_case(sanitize::sanitize_file_name(executable_name)?, case);
+
+    let app_folder = root.join(app_id.to_string());
+    let folder = sanitize::contain(&app_folder, &sanitized.to_path_buf())?;
+    let executable = folder.join(file_name);
+
+    Ok(GamePaths {
+        app_id,
+        folder,
+        executable,
+        sanitized,
+    })
+}
+
+/// Checks that the folder picked by [`resolve`] is as deep as the game list entry. The
+/// frontend counts `segments` as the entry's path components including the file name.
+pub fn check_depth(paths: &GamePaths, segments: usize) -> Result<(), sanitize::SanitizeError> {
+    let app_folder = games_root().join(paths.app_id.to_string());
+    // `resolve` keeps the folder inside the app folder, so the prefix always matches.
+    let folders = paths
+        .folder
+        .strip_prefix(&app_folder)
+        .map_or(0, |relative| relative.components().count());
+    if folders + 1 != segments {
+        return Err(sanitize::SanitizeError::DepthMismatch { segments, folders });
+    }
+    Ok(())
+}
+
+pub fn is_installed(app_id: i64, path: &str, executable_name: &str) -> bool {
+    resolve(app_id, path, executable_name).is_ok_and(|paths| paths.executable.is_file())
+}
+
+/// A dummy executable found on disk under the games root.
+#[derive(Serialize, Clone, Debug)]
+pub struct InstalledGame {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    /// Folder relative to `games/<app_id>`, `/`-separated. Empty when the executable sits
+    /// directly in the game folder.
+    pub path: String,
+    pub filename: String,
+    pub size: u64,
+    /// Unix timestamp (seconds). Falls back to the modification time where the filesystem
+    /// doesn't record creation times.
+    pub created_at: Option<u64>,
+    /// `None` when it can't be told from the file, see `install::detect_method`.
+    pub method: Option<InstallMethod>,
+    /// Details below come from the game's manifest and are `None` for installs made
+    /// before manifests existed.
+    pub name: Option<String>,
+    pub detectable: Option<String>,
+    pub template_sha256: Option<String>,
+}
+
+impl InstalledGame {
+    /// Bytes this install takes up on its own. Links share the template's storage.
+    pub fn owned_bytes(&self) -> u64 {
+        match self.method {
+            Some(InstallMethod::Hardlink) | Some(InstallMethod::Symlink) => 0,
+            _ => self.size,
+        }
+    }
+}
+
+fn unix_secs(time: std::io::Result<std::time::SystemTime>) -> Option<u64> {
+    time.ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// Collects every file below `dir`. Dot-files are bookkeeping, not dummy executables.
+fn collect_executables(
+    app_id: i64,
+    dir: &Path,
+    relative: &mut Vec<String>,
+    found: &mut Vec<InstalledGame>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            relative.push(name);
+            collect_executables(app_id, &path, relative, found)?;
+            relative.pop();
+        } else if metadata.is_file() || metadata.file_type().is_symlink() {
+            // Size and times of a symlinked install are the template's; a dangling link
+            // (template moved or upgraded) still shows up, with a size of 0.
+            let target = std::fs::metadata(&path).unwrap_or(metadata);
+            found.push(InstalledGame {
+                app_id,
+                path: relative.join("/"),
+                filename: name,
+                size: if target.is_file() { target.len() } else { 0 },
+                created_at: unix_secs(target.created()).or(unix_secs(target.modified())),
+                method: install::detect_method(&path),
+                name: None,
+                detectable: None,
+                template_sha256: None,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The manifest knows what the file alone can't tell, and is trusted over the file's
+/// times since copies and migrations reset those.
+fn apply_manifest(game: &mut InstalledGame, manifest: &manifest::GameManifest) {
+    game.name = manifest.name.clone();
+    let Some(entry) = manifest::find(manifest, &game.path, &game.filename) else {
+        return;
+    };
+    game.detectable = Some(entry.detectable.clone());
+    game.template_sha256 = Some(entry.template_sha256.clone());
+    game.created_at = Some(entry.created_at);
+    game.method = game.method.or(Some(entry.method));
+}
+
+/// Scans the games root for installed dummy executables.
+pub fn list_installed() -> Result<Vec<InstalledGame>, String> {
+    let root = games_root();
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let entries =
+        std::fs::read_dir(&root).map_err(|e| format!("Failed to read {:?}: {}", root, e))?;
+    let mut found = Vec::new();
+    for entry in entries.flatten() {
+        let app_id = match entry.file_name().to_str().map(str::parse::<i64>) {
+            Some(Ok(app_id)) => app_id,
+            _ => continue,
+        };
+        if !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            continue;
+        }
+        let mut executables = Vec::new();
+        collect_executables(app_id, &entry.path(), &mut Vec::new(), &mut executables)
+            .map_err(|e| format!("Failed to read {:?}: {}", entry.path(), e))?;
+        if let Some(manifest) = manifest::load(app_id) {
+            for game in &mut executables {
+                apply_manifest(game, &manifest);
+            }
+        }
+        found.extend(executables);
+    }
+
+    found.sort_by(|a, b| (a.app_id, &a.path, &a.filename).cmp(&(b.app_id, &b.path, &b.filename)));
+    Ok(found)
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct RemovalReport {
+    /// Removed files or folders, relative to the games root.
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+    pub skipped: Vec<SkippedGame>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GameDiskUsage {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub bytes: u64,
+    pub executables: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DiskUsageReport {
+    pub root: PathBuf,
+    pub total_bytes: u64,
+    pub games: Vec<GameDiskUsage>,
+}
+
+/// Removes the now empty folders between `from` and `stop_at`, leaving `stop_at` itself.
+fn remove_empty_parents(from: &Path, stop_at: &Path) {
+    let mut dir = from.parent();
+    while let Some(current) = dir {
+        if current == stop_at || !current.starts_with(stop_at) {
+            break;
+        }
+        if std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+pub fn relative_to_root(path: &Path) -> String {
+    path.strip_prefix(games_root())
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn running_executables(app_id: i64) -> Vec<String> {
+    process::list()
+        .into_iter()
+        .filter(|process| process.app_id == app_id)
+        .map(|process| process.executable)
+        .collect()
+}
+
+pub fn installed_path(game: &InstalledGame) -> PathBuf {
+    let mut path = games_root().join(game.app_id.to_string());
+    path.extend(game.path.split('/').filter(|part| !part.is_empty()));
+    path.join(&game.filename)
+}
+
+/// Paths of an installed executable as they are on disk. Unlike [`resolve`], the current
+/// path case setting isn't applied, so they still match after it changed.
+pub fn installed_paths(game: &InstalledGame) -> GamePaths {
+    let executable = installed_path(game);
+    GamePaths {
+        app_id: game.app_id,
+        folder: executable
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+        sanitized: sanitize::SanitizedPath {
+            components: game
+                .path
+                .split('/')
+                .filter(|part| !part.is_empty())
+                .map(str::to_string)
+                .collect(),
+            changes: Vec::new(),
+        },
+        executable,
+    }
+}
+
+/// Removes one executable of a game, or the whole `games/<app_id>` folder when `executable`
+/// is `None`. Refuses while a runner for it is running.
+pub fn uninstall(app_id: i64, executable: Option<&str>) -> Result<RemovalReport, String> {
+    let running = running_executables(app_id);
+    let mut report = RemovalReport::default();
+
+    match executable {
+        None => {
+            if let Some(executable) = running.first() {
+                return Err(format!(
+                    "Can't uninstall app {} while {} is running",
+                    app_id, executable
+                ));
+            }
+            let folder = games_root().join(app_id.to_string());
+            if !folder.is_dir() {
+                return Err(format!("App {} is not installed", app_id));
+            }
+            let bytes = list_installed()?
+                .iter()
+                .filter(|game| game.app_id == app_id)
+                .map(InstalledGame::owned_bytes)
+                .sum();
+            std::fs::remove_dir_all(&folder)
+                .map_err(|e| format!("Failed to remove {:?}: {}", folder, e))?;
+            report.removed.push(relative_to_root(&folder));
+            report.bytes_freed = bytes;
+        }
+        Some(executable) => {
+            let file_name = sanitize::sanitize_file_name(executable).map_err(|e| e.to_string())?;
+            if running.contains(&file_name.to_lowercase()) {
+                return Err(format!("Can't uninstall {} while it is running", file_name));
+            }
+            let matches: Vec<InstalledGame> = list_installed()?
+                .into_iter()
+                .filter(|game| {
+                    game.app_id == app_id
+                        && game.filename.to_lowercase() == file_name.to_lowercase()
+                })
+                .collect();
+            if matches.is_empty() {
+                return Err(format!("{} is not installed for app {}", file_name, app_id));
+            }
+            for game in matches {
+                let path = installed_path(&game);
+                std::fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+                manifest::forget(app_id, &game.path, &game.filename);
+                heartbeat::remove(&path);
+                remove_empty_parents(&path, games_root().as_path());
+                report.removed.push(relative_to_root(&path));
+                report.bytes_freed += game.owned_bytes();
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Removes every dummy executable created more than `older_than` ago, skipping running ones.
+pub fn prune(older_than: Duration) -> Result<RemovalReport, String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let cutoff = now.saturating_sub(older_than.as_secs());
+    let running = process::list();
+    let mut report = RemovalReport::default();
+
+    for game in list_installed()? {
+        if game.created_at.is_none_or(|created| created > cutoff) {
+            continue;
+        }
+        if running.iter().any(|process| {
+            process.app_id == game.app_id && process.executable == game.filename.to_lowercase()
+        }) {
+            report.skipped.push(SkippedGame {
+                app_id: game.app_id.to_string(),
+                reason: format!("{} is running", game.filename),
+            });
+            continue;
+        }
+
+        let path = installed_path(&game);
+        match std::fs::remove_file(&path) {
+            Ok(_) => {
+                manifest::forget(game.app_id, &game.path, &game.filename);
+                heartbeat::remove(&path);
+                remove_empty_parents(&path, games_root().as_path());
+                report.removed.push(relative_to_root(&path));
+                report.bytes_freed += game.owned_bytes();
+            }
+            Err(e) => report.skipped.push(SkippedGame {
+                app_id: game.app_id.to_string(),
+                reason: format!("Failed to remove {:?}: {}", path, e),
+            }),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Totals the games root per app id.
+pub fn disk_usage() -> Result<DiskUsageReport, String> {
+    let mut games: Vec<GameDiskUsage> = Vec::new();
+    for game in list_installed()? {
+        match games.iter_mut().find(|usage| usage.app_id == game.app_id) {
+            Some(usage) => {
+                usage.bytes += game.owned_bytes();
+                usage.executables += 1;
+            }
+            None => games.push(GameDiskUsage {
+                app_id: game.app_id,
+                bytes: game.owned_bytes(),
+                executables: 1,
+            }),
+        }
+    }
+
+    Ok(DiskUsageReport {
+        root: games_root(),
+        total_bytes: games.iter().map(|usage| usage.bytes).sum(),
+        games,
+    })
+}
+
+/// Starts the installed dummy executable as a tracked runner.
+pub fn launch(
+    handle: &AppHandle,
+    name: &str,
+    path: &str,
+    executable_name: &str,
+    app_id: i64,
+    duration: Option<Duration>,
+    activity: Option<&runner_core::Activity>,
+) -> Result<u32, String> {
+    let paths = resolve(app_id, path, executable_name).map_err(|e| e.to_string())?;
+    // const DETACHED_PROCESS: u32 = 0x00000008;
+    // const CREATE_NO_WINDOW: u32 = 0x08000000; // Hide the window
+    // Installs stay as they are until the user upgrades them.
+    let flags = if upgrade::is_current(&paths) {
+        process::RunnerFlags::Current
+    } else {
+        println!(
+            "{:?} predates the bundled runner, starting it with its title only",
+            paths.executable
+        );
+        process::RunnerFlags::TitleOnly
+    };
+    process::spawn(handle, name, &paths, duration, activity, flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_app_id_folders_are_migrated() {
+        assert!(is_app_folder("1158877933042143272"));
+        assert!(is_app_folder("-1"));
+        for name in [".staging", "", "+5", "007", "1.5", "Games", "12 "] {
+            assert!(!is_app_folder(name), "{:?}", name);
+        }
+    }
+
+    // Needs a case-sensitive filesystem to tell the two installs apart.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn installs_are_found_after_the_path_case_changes() {
+        let root = std::env::temp_dir().join(format!("games-case-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let lowercase = sanitize::PathCase::Lowercase;
+
+        let fresh = resolve_in(&root, 42, "Bin/Win64", "Game.exe", lowercase).unwrap();
+        assert_eq!(fresh.executable, root.join("42/bin/win64/game.exe"));
+
+        std::fs::create_dir_all(root.join("42/Bin/Win64")).unwrap();
+        std::fs::write(root.join("42/Bin/Win64/Game.exe"), "").unwrap();
+        let existing = resolve_in(&root, 42, "Bin/Win64", "Game.exe", lowercase).unwrap();
+        assert_eq!(existing.executable, root.join("42/Bin/Win64/Game.exe"));
+        assert_eq!(existing.folder, root.join("42/Bin/Win64"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+//! Reads the status files runners keep next to their executable.
+//!
+//! A running runner rewrites its file every [`HEARTBEAT_INTERVAL`] and marks it
+//! `exited` on the way out, so the files tell which runners are still alive even after
+//! the app or the webview restarted and lost its in-memory state.
+
+pub use runner_core::status::file_path as status_file_path;
+use runner_core::status::{self, HEARTBEAT_INTERVAL};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::games;
+use crate::process::{self, ProcessKey};
+
+/// A running runner whose heartbeat is older than this is considered gone.
+const STALE_AFTER: Duration = Duration::from_secs(3 * HEARTBEAT_INTERVAL.as_secs());
+
+/// Contents of a runner's status file. Runners from before the heartbeat only wrote the
+/// exit fields, so everything else is optional.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct StatusFile {
+    pub pid: Option<u32>,
+    pub app_id: Option<u64>,
+    pub title: Option<String>,
+    pub started_at: Option<u64>,
+    pub heartbeat_at: Option<u64>,
+    pub state: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Liveness {
+    /// Heartbeat is recent and the process exists.
+    Live,
+    /// Says it is running, but the heartbeat stopped or the process is gone: the runner
+    /// crashed or was killed.
+    Stale,
+    /// The runner recorded its exit.
+    Exited,
+}
+
+/// A runner found through its status file.
+#[derive(Serialize, Clone, Debug)]
+pub struct RunnerRecord {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    /// Folder relative to `games/<app_id>`, like [`games::InstalledGame::path`].
+    pub path: String,
+    /// Lowercased like [`ProcessKey::executable`].
+    pub executable: String,
+    pub pid: Option<u32>,
+    pub title: Option<String>,
+    pub started_at: Option<u64>,
+    pub heartbeat_at: Option<u64>,
+    pub liveness: Liveness,
+    pub reason: Option<String>,
+    /// Whether this app instance spawned it and can stop it.
+    pub tracked: bool,
+}
+
+/// Reads a runner's status file. Older runners don't write one.
+pub fn read(path: &Path) -> Option<StatusFile> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(status) => Some(status),
+        Err(e) => {
+            eprintln!("Failed to parse runner status {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Removes the status file next to `executable`, and the temporary file a runner killed
+/// mid-write leaves behind.
+pub fn remove(executable: &Path) {
+    let path = status_file_path(executable);
+    let _ = std::fs::remove_file(status::temp_path(&path));
+    let _ = std::fs::remove_file(path);
+}
+
+/// Whether a process with `pid` exists. Without a way to tell, it is assumed to, and the
+/// heartbeat alone decides.
+fn pid_exists(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return false;
+        };
+        if unsafe { libc::kill(pid, 0) } == 0 {
+            return true;
+        }
+        std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+pub fn liveness(status: &StatusFile, now: u64, pid_exists: impl Fn(u32) -> bool) -> Liveness {
+    if status.state.as_deref() != Some("running") {
+        return Liveness::Exited;
+    }
+    let fresh = status
+        .heartbeat_at
+        .is_some_and(|beat| now.saturating_sub(beat) <= STALE_AFTER.as_secs());
+    if fresh && status.pid.is_some_and(pid_exists) {
+        Liveness::Live
+    } else {
+        Liveness::Stale
+    }
+}
+
+/// Every runner that left a status file next to an installed executable.
+pub fn scan() -> Result<Vec<RunnerRecord>, String> {
+    let now = status::unix_now();
+    let mut records = Vec::new();
+
+    for game in games::list_installed()? {
+        let executable = games::installed_path(&game);
+        let Some(status) = read(&status_file_path(&executable)) else {
+            continue;
+        };
+        let key = ProcessKey::new(game.app_id, &game.filename);
+        records.push(RunnerRecord {
+            app_id: game.app_id,
+            path: game.path,
+            tracked: process::is_running(&key),
+            executable: key.executable,
+            pid: status.pid,
+            title: status.title.clone(),
+            started_at: status.started_at,
+            heartbeat_at: status.heartbeat_at,
+            liveness: liveness(&status, now, pid_exists),
+            reason: status.reason,
+        });
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn running(heartbeat_at: u64) -> StatusFile {
+        StatusFile {
+            pid: Some(4242),
+            heartbeat_at: Some(heartbeat_at),
+            state: Some("running".to_string()),
+            ..StatusFile::default()
+        }
+    }
+
+    #[test]
+    fn recent_heartbeat_of_an_existing_process_is_live() {
+        let status = running(NOW - HEARTBEAT_INTERVAL.as_secs());
+        assert_eq!(liveness(&status, NOW, |pid| pid == 4242), Liveness::Live);
+    }
+
+    #[test]
+    fn old_heartbeat_or_missing_process_is_stale() {
+        let old = running(NOW - STALE_AFTER.as_secs() - 1);
+        assert_eq!(liveness(&old, NOW, |_| true), Liveness::Stale);
+
+        let gone = running(NOW);
+        assert_eq!(liveness(&gone, NOW, |_| false), Liveness::Stale);
+
+        let silent = StatusFile {
+            heartbeat_at: None,
+            ..running(NOW)
+        };
+        assert_eq!(liveness(&silent, NOW, |_| true), Liveness::Stale);
+    }
+
+    #[test]
+    fn recorded_exit_is_dead_whatever_the_heartbeat() {
+        let exited = StatusFile {
+            state: Some("exited".to_string()),
+            ..running(NOW)
+        };
+        assert_eq!(liveness(&exited, NOW, |_| true), Liveness::Exited);
+        // Runners from before the heartbeat only wrote the exit fields.
+        assert_eq!(
+            liveness(&StatusFile::default(), NOW, |_| true),
+            Liveness::Exited
+        );
+    }
+}
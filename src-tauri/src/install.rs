@@ -0,0 +1,473 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::sanitize::SanitizeError;
+use crate::template::TemplateError;
+use crate::{games, manifest, process, settings, template};
+
+/// How the runner template ends up at a game's executable path.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallMethod {
+    /// A full copy of the template.
+    #[default]
+    Copy,
+    /// A hard link to the template; costs no extra space but must be on the same volume.
+    Hardlink,
+    /// A symbolic link to the template. Needs Developer Mode or admin rights on Windows,
+    /// and the OS may report the template's path as the process image, which Discord
+    /// won't recognise.
+    Symlink,
+}
+
+impl InstallMethod {
+    /// Methods to try, in order, when this one is selected.
+    fn fallbacks(self) -> &'static [InstallMethod] {
+        match self {
+            InstallMethod::Copy => &[InstallMethod::Copy],
+            InstallMethod::Hardlink => &[InstallMethod::Hardlink, InstallMethod::Copy],
+            InstallMethod::Symlink => &[
+                InstallMethod::Symlink,
+                InstallMethod::Hardlink,
+                InstallMethod::Copy,
+            ],
+        }
+    }
+}
+
+#[cfg(unix)]
+fn symlink_file(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink_file(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+/// Bundlers don't always keep the template's executable bit, and a copy needs it to run.
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Links share the template's permissions, so it has to be executable itself. When it
+/// can't be made so (read-only resources), the link fails and the next method is tried.
+#[cfg(unix)]
+fn ensure_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    if std::fs::metadata(path)?.permissions().mode() & 0o111 == 0o111 {
+        return Ok(());
+    }
+    make_executable(path)
+}
+
+#[cfg(not(unix))]
+fn ensure_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Puts the template at `target` with `method`, falling back to the next method when the
+/// filesystem refuses (links across devices, no symlink privilege, FAT volumes...).
+/// Returns the method that worked.
+fn place_template(
+    template: &Path,
+    target: &Path,
+    method: InstallMethod,
+) -> std::io::Result<InstallMethod> {
+    let mut last_error = None;
+    for &attempt in method.fallbacks() {
+        let result = match attempt {
+            InstallMethod::Copy => {
+                std::fs::copy(template, target).and_then(|_| make_executable(target))
+            }
+            InstallMethod::Hardlink => {
+                ensure_executable(template).and_then(|_| std::fs::hard_link(template, target))
+            }
+            InstallMethod::Symlink => {
+                ensure_executable(template).and_then(|_| symlink_file(template, target))
+            }
+        };
+        match result {
+            Ok(()) => return Ok(attempt),
+            Err(e) => {
+                println!("Failed to install with {:?}: {}", attempt, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| std::io::Error::other("no install method to try")))
+}
+
+#[derive(Debug)]
+pub enum InstallError {
+    Path(SanitizeError),
+    Template(TemplateError),
+    /// The dummy executable is running, so it can't be replaced.
+    Running {
+        app_id: i64,
+        executable: String,
+    },
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl fmt::Display for InstallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstallError::Path(e) => write!(f, "{}", e),
+            InstallError::Template(e) => write!(f, "{}", e),
+            InstallError::Running { app_id, executable } => write!(
+                f,
+                "{} (app {}) is running, stop it before reinstalling",
+                executable, app_id
+            ),
+            InstallError::Io {
+                action,
+                path,
+                source,
+            } => write!(f, "Failed to {} {:?}: {}", action, path, source),
+        }
+    }
+}
+
+impl std::error::Error for InstallError {}
+
+impl From<SanitizeError> for InstallError {
+    fn from(e: SanitizeError) -> Self {
+        InstallError::Path(e)
+    }
+}
+
+impl From<TemplateError> for InstallError {
+    fn from(e: TemplateError) -> Self {
+        InstallError::Template(e)
+    }
+}
+
+/// Staging folders live under the games root so the final rename never crosses filesystems.
+const STAGING_DIR: &str = ".staging";
+
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Removes staging folders left behind by a crash. Only safe before any install starts.
+pub fn clean_staging() {
+    let staging = games::games_root().join(STAGING_DIR);
+    if staging.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&staging) {
+            eprintln!("Failed to clean {:?}: {}", staging, e);
+        }
+    }
+}
+
+/// Whether replacing the file failed because the executable is running: Windows refuses
+/// to touch a loaded image, and only says access denied when the target exists.
+#[cfg(windows)]
+fn is_busy_error(e: &std::io::Error, target_exists: bool) -> bool {
+    match e.raw_os_error() {
+        Some(32) => true,         // ERROR_SHARING_VIOLATION
+        Some(5) => target_exists, // ERROR_ACCESS_DENIED
+        _ => false,
+    }
+}
+
+/// Whether replacing the file failed because the executable is running; Linux refuses to
+/// write to a loaded image.
+#[cfg(unix)]
+fn is_busy_error(e: &std::io::Error, _target_exists: bool) -> bool {
+    e.raw_os_error() == Some(libc::ETXTBSY)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_busy_error(_e: &std::io::Error, _target_exists: bool) -> bool {
+    false
+}
+
+/// Creates `folder` and returns the folders that didn't exist yet, deepest first,
+/// so a failed install can remove exactly what it created.
+fn create_folder(folder: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let missing: Vec<PathBuf> = folder
+        .ancestors()
+        .take_while(|dir| !dir.exists())
+        .map(Path::to_path_buf)
+        .collect();
+    std::fs::create_dir_all(folder)?;
+    Ok(missing)
+}
+
+/// Optional details about the game from the game list.
+#[derive(Default, Clone, Copy)]
+pub struct GameDetails<'a> {
+    pub name: Option<&'a str>,
+    /// The executable entry as it appears in the detectable game list.
+    pub detectable: Option<&'a str>,
+    /// Path components of the entry including the file name; checked against the folder
+    /// depth, see `games::check_depth`.
+    pub segments: Option<usize>,
+}
+
+/// The frontend sends the entry's segment count as `path_len`; anything below 1 means
+/// it didn't know it and the depth check is skipped.
+pub fn segments(path_len: i64) -> Option<usize> {
+    usize::try_from(path_len)
+        .ok()
+        .filter(|segments| *segments > 0)
+}
+
+/// Installs the runner template to `games/<app_id>/<path>/<executable_name>` using the
+/// configured install method, and records it in the game's manifest.
+///
+/// The executable is staged first and renamed into place, so a failure at any point
+/// leaves neither a half-written file nor folders that weren't there before.
+pub fn install(
+    handle: &AppHandle,
+    path: &str,
+    executable_name: &str,
+    app_id: i64,
+    details: GameDetails,
+) -> Result<PathBuf, InstallError> {
+    let paths = games::resolve(app_id, path, executable_name)?;
+    if let Some(segments) = details.segments {
+        games::check_depth(&paths, segments)?;
+    }
+    install_at(handle, paths, details)
+}
+
+/// Replaces an installed executable with the current template where it already is, even
+/// if the path case setting would put a fresh install somewhere else.
+pub fn reinstall(
+    handle: &AppHandle,
+    game: &games::InstalledGame,
+    details: GameDetails,
+) -> Result<PathBuf, InstallError> {
+    install_at(handle, games::installed_paths(game), details)
+}
+
+fn install_at(
+    handle: &AppHandle,
+    paths: games::GamePaths,
+    details: GameDetails,
+) -> Result<PathBuf, InstallError> {
+    let app_id = paths.app_id;
+    let file_name = paths.file_name();
+
+    if process::is_running(&paths.process_key()) {
+        return Err(InstallError::Running {
+            app_id,
+            executable: file_name,
+        });
+    }
+
+    println!("Game folder path: {:?}", paths.folder);
+    println!("Game full path: {:?}", paths.executable);
+
+    let template = template::resolve(handle)?;
+    println!("Creating dummy game executable: {:?}", template.path);
+
+    let staging = games::games_root().join(STAGING_DIR).join(format!(
+        "{}-{}-{}",
+        app_id,
+        std::process::id(),
+        STAGING_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = stage_and_commit(&template.path, &staging, &paths.folder, &paths.executable);
+
+    if let Err(e) = std::fs::remove_dir_all(&staging) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("Failed to clean {:?}: {}", staging, e);
+        }
+    }
+
+    match result {
+        Ok(method) => {
+            println!("Installed {:?} with {:?}", paths.executable, method);
+
+            let relative = paths.sanitized.components.join("/");
+            let entry = manifest::ManifestExecutable {
+                detectable: details.detectable.map(str::to_string).unwrap_or_else(|| {
+                    [relative.as_str(), file_name.as_str()]
+                        .iter()
+                        .filter(|part| !part.is_empty())
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join("/")
+                }),
+                path: relative,
+                filename: file_name,
+                template: template.name.to_string(),
+                template_sha256: template.sha256.to_string(),
+                method,
+                created_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+                app_version: handle.package_info().version.to_string(),
+            };
+            // The executable is in place either way; a missing manifest only means
+            // later features fall back to inspecting the file.
+            if let Err(e) = manifest::record(app_id, details.name, entry) {
+                eprintln!("{}", e);
+            }
+
+            Ok(paths.executable)
+        }
+        Err(InstallError::Io { source, .. })
+            if is_busy_error(&source, paths.executable.exists()) =>
+        {
+            Err(InstallError::Running {
+                app_id,
+                executable: file_name,
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn stage_and_commit(
+    template: &Path,
+    staging: &Path,
+    folder: &Path,
+    executable: &Path,
+) -> Result<InstallMethod, InstallError> {
+    std::fs::create_dir_all(staging).map_err(|source| InstallError::Io {
+        action: "create staging folder",
+        path: staging.to_path_buf(),
+        source,
+    })?;
+
+    let staged = staging.join(executable.file_name().unwrap_or_default());
+    let method = settings::get().install_method;
+    let used = place_template(template, &staged, method).map_err(|source| InstallError::Io {
+        action: "stage dummy executable",
+        path: staged.clone(),
+        source,
+    })?;
+    if used != method {
+        println!("Fell back from {:?} to {:?}", method, used);
+    }
+
+    let created = create_folder(folder).map_err(|source| InstallError::Io {
+        action: "create game folder",
+        path: folder.to_path_buf(),
+        source,
+    })?;
+
+    // `rename` replaces an existing file (or link) in one step.
+    if let Err(source) = std::fs::rename(&staged, executable) {
+        for dir in &created {
+            let _ = std::fs::remove_dir(dir);
+        }
+        return Err(InstallError::Io {
+            action: "move dummy executable to",
+            path: executable.to_path_buf(),
+            source,
+        });
+    }
+
+    Ok(used)
+}
+
+/// Where the template an install would copy from comes from.
+#[derive(Serialize, Clone, Debug)]
+pub struct TemplateSource {
+    pub name: &'static str,
+    pub path: Option<PathBuf>,
+    pub sha256: Option<&'static str>,
+    /// Why the template can't be used right now; the install would fail with this.
+    pub error: Option<String>,
+}
+
+/// What `install` and `games::launch` would do for a game executable.
+#[derive(Serialize, Clone, Debug)]
+pub struct InstallPlan {
+    /// Absolute folder the executable goes into, also the runner's working directory.
+    pub folder: PathBuf,
+    pub executable: PathBuf,
+    pub filename: String,
+    pub template: TemplateSource,
+    pub method: InstallMethod,
+    /// Adjustments made to the game list's path, see `sanitize::sanitize_dir`.
+    pub changes: Vec<String>,
+    pub installed: bool,
+    pub running: bool,
+}
+
+/// Resolves an install the same way `install` does, without touching the disk.
+pub fn plan(
+    handle: &AppHandle,
+    path: &str,
+    executable_name: &str,
+    app_id: i64,
+    segments: Option<usize>,
+) -> Result<InstallPlan, String> {
+    let paths = games::resolve(app_id, path, executable_name).map_err(|e| e.to_string())?;
+    if let Some(segments) = segments {
+        games::check_depth(&paths, segments).map_err(|e| e.to_string())?;
+    }
+    let filename = paths.file_name();
+
+    let template = match template::resolve(handle) {
+        Ok(template) => TemplateSource {
+            name: template.name,
+            path: Some(template.path),
+            sha256: Some(template.sha256),
+            error: None,
+        },
+        Err(e) => {
+            let entry = template::entry(template::CURRENT_TEMPLATE).ok();
+            TemplateSource {
+                name: template::CURRENT_TEMPLATE,
+                path: None,
+                sha256: entry.and_then(|entry| entry.sha256),
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    let running = process::is_running(&paths.process_key());
+    let folder = std::path::absolute(&paths.folder).unwrap_or(paths.folder);
+    Ok(InstallPlan {
+        installed: paths.executable.is_file(),
+        running,
+        executable: folder.join(&filename),
+        folder,
+        filename,
+        template,
+        method: settings::get().install_method,
+        changes: paths.sanitized.changes,
+    })
+}
+
+/// Works out how an existing dummy executable was installed from the file itself.
+/// Hard links can only be told apart from copies on Unix, so this is `None` elsewhere.
+pub fn detect_method(path: &Path) -> Option<InstallMethod> {
+    let metadata = path.symlink_metadata().ok()?;
+    if metadata.file_type().is_symlink() {
+        return Some(InstallMethod::Symlink);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if metadata.nlink() > 1 {
+            Some(InstallMethod::Hardlink)
+        } else {
+            Some(InstallMethod::Copy)
+        }
+    }
+    #[cfg(not(unix))]
+    None
+}
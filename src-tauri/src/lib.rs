@@ -1,12 +1,26 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use once_cell::sync::OnceCell;
-use std::env;
-use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Mutex;
-use tauri::{path::BaseDirectory, AppHandle, Emitter, Listener, Manager};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Listener};
 
+mod app_id;
+mod batch;
+mod control;
+mod games;
+mod heartbeat;
+mod install;
+mod manifest;
+mod process;
+mod queue;
+mod rotation;
 mod rpc;
 mod runner;
+mod sanitize;
+mod settings;
+mod template;
+mod upgrade;
 
 // Global static instance of the Discord client
 static DISCORD_CLIENT: OnceCell<Mutex<Option<rpc::Client>>> = OnceCell::new();
@@ -26,100 +40,264 @@ async fn create_fake_game(
     path: &str,
     executable_name: &str,
     path_len: i64,
-    app_id: i64,
+    app_id: String,
+    name: Option<String>,
+    detectable: Option<String>,
 ) -> Result<String, String> {
-    // Must create in the same directory as the executable to avoid permission issues
-    // Get the executable directory to look for config file
-    let exe_path: std::path::PathBuf = env::current_exe().unwrap_or_default();
-    let exe_dir = exe_path.parent().unwrap_or_else(|| Path::new(""));
-
-    let normalized_path = Path::new(path).to_string_lossy().to_string();
-
-    let game_folder_path = exe_dir
-        .join("games")
-        .join(app_id.to_string())
-        .join(normalized_path);
-
-    println!("Game folder path: {:?}", game_folder_path);
-    println!(
-        "Game full path: {:?}",
-        game_folder_path.join(executable_name)
-    );
-
-    // Ok(format!("Dummy executable copied to: {:?}", target_executable_path))
-    match std::fs::create_dir_all(&game_folder_path) {
-        Ok(_) => {
-            println!("Successfully created directory: {:?}", game_folder_path);
-        }
-        Err(e) => return Err(format!("Failed to create game folder: {}", e)),
+    let app_id = app_id::parse(&app_id)?;
+    let details = install::GameDetails {
+        name: name.as_deref(),
+        detectable: detectable.as_deref(),
+        segments: install::segments(path_len),
     };
-    // copy the dummy executable to the created folder
-    // there is a `template.exe` file along the final build.
-    let resource_path = handle
-        .path()
-        .resolve("data/src-win.exe", BaseDirectory::Resource)
-        .unwrap_or_default();
-
-    println!("Creating dummy game executable: {:?}", resource_path);
-    let dummy_executable_path = exe_dir.join("template.exe");
-    let target_executable_path = game_folder_path.join(executable_name);
-    match std::fs::copy(&resource_path, &target_executable_path) {
-        Ok(_) => Ok(format!(
-            "Dummy executable copied to: {:?}",
-            target_executable_path
-        )),
-        Err(e) => Err(format!("Failed to copy dummy executable: {}", e)),
-    }
+    let target_executable_path = install::install(&handle, path, executable_name, app_id, details)
+        .map_err(|e| e.to_string())?;
+    Ok(format!(
+        "Dummy executable copied to: {:?}",
+        target_executable_path
+    ))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn plan_install(
+    handle: tauri::AppHandle,
+    path: &str,
+    executable_name: &str,
+    path_len: i64,
+    app_id: String,
+) -> Result<install::InstallPlan, String> {
+    let app_id = app_id::parse(&app_id)?;
+    install::plan(
+        &handle,
+        path,
+        executable_name,
+        app_id,
+        install::segments(path_len),
+    )
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn create_fake_games(
+    handle: tauri::AppHandle,
+    games: Vec<batch::BatchInstallRequest>,
+) -> batch::BatchInstallSummary {
+    batch::install_all(&handle, games)
 }
 
 #[tauri::command(rename_all = "snake_case")]
 async fn run_background_process(
+    handle: tauri::AppHandle,
     name: &str,
     path: &str,
     executable_name: &str,
     path_len: i64,
-    app_id: i64,
+    app_id: String,
+    duration: Option<u64>,
+    activity_json: Option<String>,
 ) -> Result<String, String> {
-    let exe_path = env::current_exe().unwrap_or_default();
-    let exe_dir = exe_path.parent().unwrap_or_else(|| Path::new(""));
-
-    let normalized_path = Path::new(path).to_string_lossy().to_string();
-
-    let game_folder_path = exe_dir
-        .join("games")
-        .join(app_id.to_string())
-        .join(normalized_path);
-    let executable_path = game_folder_path.join(executable_name);
-    // const DETACHED_PROCESS: u32 = 0x00000008;
-    // const CREATE_NO_WINDOW: u32 = 0x08000000; // Hide the window
-    match std::process::Command::new(&executable_path)
-        .args(["--title", name])
-        .current_dir(game_folder_path) // Set working directory to the game folder
-        .spawn()
-    {
-        Ok(_) => Ok("Process started successfully".to_string()),
-        Err(e) => Err(format!("Failed to start process: {}", e)),
+    let app_id = app_id::parse(&app_id)?;
+    if let Some(segments) = install::segments(path_len) {
+        let paths = games::resolve(app_id, path, executable_name).map_err(|e| e.to_string())?;
+        games::check_depth(&paths, segments).map_err(|e| e.to_string())?;
     }
+    let activity = match activity_json {
+        Some(json) => {
+            let params = runner::parse_activity_json(&json)?;
+            let (activity_app_id, activity) = runner::runner_activity(&params)?;
+            // The runner sets the activity for the app it was launched as.
+            if u64::try_from(app_id).ok() != Some(activity_app_id) {
+                return Err(format!(
+                    "The activity is for app {} but app {} is being launched",
+                    activity_app_id, app_id
+                ));
+            }
+            Some(activity)
+        }
+        None => None,
+    };
+    let pid = games::launch(
+        &handle,
+        name,
+        path,
+        executable_name,
+        app_id,
+        duration.map(Duration::from_secs),
+        activity.as_ref(),
+    )?;
+    Ok(format!("Process started successfully with PID {}", pid))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn stop_process(
+    handle: tauri::AppHandle,
+    app_id: String,
+    exec_name: String,
+    grace_period_ms: Option<u64>,
+) -> Result<process::StopOutcome, String> {
+    let app_id = app_id::parse(&app_id)?;
+    let grace_period = grace_period_ms
+        .map(Duration::from_millis)
+        .unwrap_or(process::DEFAULT_GRACE_PERIOD);
+    process::stop(
+        &handle,
+        &process::ProcessKey::for_executable(app_id, &exec_name),
+        grace_period,
+    )
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn list_processes() -> Vec<process::ProcessInfo> {
+    process::list()
 }
 
 #[tauri::command(rename_all = "snake_case")]
-async fn stop_process(exec_name: String) -> Result<(), String> {
-    // Stop the process using taskkill command
-    let output = std::process::Command::new("taskkill")
-        .arg("/F")
-        .arg("/IM")
-        .arg(exec_name)
-        .output()
-        .map_err(|e| format!("Failed to execute taskkill: {}", e))?;
+async fn list_runner_states() -> Result<Vec<heartbeat::RunnerRecord>, String> {
+    heartbeat::scan()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn get_runner_status(
+    app_id: String,
+    exec_name: String,
+) -> Result<control::RunnerStatus, String> {
+    let app_id = app_id::parse(&app_id)?;
+    process::runner_status(&process::ProcessKey::for_executable(app_id, &exec_name))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn rename_runner(app_id: String, exec_name: String, title: String) -> Result<String, String> {
+    let app_id = app_id::parse(&app_id)?;
+    process::rename(
+        &process::ProcessKey::for_executable(app_id, &exec_name),
+        &title,
+    )
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn enqueue_sessions(
+    handle: AppHandle,
+    entries: Vec<queue::QueueEntryRequest>,
+) -> Vec<queue::QueueEntry> {
+    queue::enqueue(&handle, entries)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_queue() -> queue::QueueSnapshot {
+    queue::snapshot()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn reorder_queue(ids: Vec<u64>) -> Result<queue::QueueSnapshot, String> {
+    queue::reorder(&ids)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn skip_queue_entry(handle: AppHandle, id: u64) -> Result<(), String> {
+    queue::skip(&handle, id)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn cancel_queue(handle: AppHandle) {
+    queue::cancel(&handle)
+}
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        Err(format!(
-            "Failed to stop process: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ))
+#[tauri::command(rename_all = "snake_case")]
+fn start_rotation(
+    handle: AppHandle,
+    games: Vec<rotation::RotationGameRequest>,
+    interval_minutes: u64,
+) -> Result<rotation::RotationState, String> {
+    let interval_secs = interval_minutes
+        .checked_mul(60)
+        .ok_or_else(|| format!("Rotation interval of {} minutes is too long", interval_minutes))?;
+    rotation::start(&handle, games, Duration::from_secs(interval_secs))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_rotation() -> Option<rotation::RotationState> {
+    rotation::state()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn stop_rotation(handle: AppHandle) {
+    rotation::stop(&handle)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn list_installed_games() -> Result<Vec<games::InstalledGame>, String> {
+    games::list_installed()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn uninstall_game(
+    app_id: String,
+    executable: Option<String>,
+) -> Result<games::RemovalReport, String> {
+    let app_id = app_id::parse(&app_id)?;
+    games::uninstall(app_id, executable.as_deref())
+}
+
+/// `older_than` is in seconds.
+#[tauri::command(rename_all = "snake_case")]
+async fn prune_games(older_than: u64) -> Result<games::RemovalReport, String> {
+    games::prune(Duration::from_secs(older_than))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn games_disk_usage() -> Result<games::DiskUsageReport, String> {
+    games::disk_usage()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn check_installed_games(handle: AppHandle) -> Result<Vec<upgrade::StaleGame>, String> {
+    upgrade::find_stale(&handle)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn upgrade_installed_games(handle: AppHandle) -> Result<upgrade::UpgradeReport, String> {
+    upgrade::upgrade(&handle)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_settings() -> settings::Settings {
+    settings::get()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_install_method(method: install::InstallMethod) -> Result<settings::Settings, String> {
+    settings::update(|settings| settings.install_method = method)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_path_case(case: sanitize::PathCase) -> Result<settings::Settings, String> {
+    settings::update(|settings| settings.path_case = case)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_games_root() -> games::GamesRoot {
+    games::games_root_info()
+}
+
+/// Changes where new games are installed. Existing installs stay where they are until
+/// `migrate_games_root` is called. `None` goes back to picking the root automatically.
+#[tauri::command(rename_all = "snake_case")]
+fn set_games_root(handle: AppHandle, path: Option<String>) -> Result<games::GamesRoot, String> {
+    let path = path.map(PathBuf::from);
+    if let Some(path) = &path {
+        if !path.is_absolute() {
+            return Err(format!("Games root {:?} must be an absolute path", path));
+        }
     }
+    settings::update(|settings| settings.games_root = path)?;
+    Ok(games::init_root(&handle))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+async fn migrate_games_root(
+    handle: AppHandle,
+    target: Option<String>,
+) -> Result<games::MigrationReport, String> {
+    games::migrate_root(&handle, target.map(PathBuf::from))
 }
 
 /// Usage: Calling from JS:
@@ -214,13 +392,45 @@ async fn fetch_gamelist_from_discord() -> tauri::ipc::Response {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .setup(|app| {
+            settings::init(app.handle());
+            games::init_root(app.handle());
+            install::clean_staging();
+            Ok(())
+        })
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             greet,
             create_fake_game,
+            create_fake_games,
+            plan_install,
             stop_process,
+            list_processes,
+            list_runner_states,
+            get_runner_status,
+            rename_runner,
+            enqueue_sessions,
+            get_queue,
+            reorder_queue,
+            skip_queue_entry,
+            cancel_queue,
+            start_rotation,
+            get_rotation,
+            stop_rotation,
+            list_installed_games,
+            uninstall_game,
+            prune_games,
+            games_disk_usage,
+            check_installed_games,
+            upgrade_installed_games,
+            get_settings,
+            set_install_method,
+            set_path_case,
+            get_games_root,
+            set_games_root,
+            migrate_games_root,
             connect_to_discord_rpc_3,
             run_background_process,
             fetch_gamelist_gh_mirror,
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::games;
+use crate::install::InstallMethod;
+
+/// Kept next to the dummy executables in `games/<app_id>/`. Dot-prefixed so the inventory
+/// doesn't mistake it for an executable.
+pub const MANIFEST_FILE: &str = ".manifest.json";
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Serialises read-modify-write cycles, since several installs can target the same game.
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// What the app knows about a game folder it created.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GameManifest {
+    pub version: u32,
+    pub app_id: i64,
+    pub name: Option<String>,
+    pub executables: Vec<ManifestExecutable>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ManifestExecutable {
+    /// Folder relative to `games/<app_id>`, `/`-separated.
+    pub path: String,
+    pub filename: String,
+    /// The executable entry exactly as it appeared in the detectable game list.
+    pub detectable: String,
+    pub template: String,
+    pub template_sha256: String,
+    pub method: InstallMethod,
+    /// Unix timestamp (seconds).
+    pub created_at: u64,
+    /// Version of the app that created the install.
+    pub app_version: String,
+}
+
+impl ManifestExecutable {
+    fn is_same(&self, path: &str, filename: &str) -> bool {
+        self.path == path && self.filename == filename
+    }
+}
+
+fn manifest_path(app_id: i64) -> PathBuf {
+    games::games_root()
+        .join(app_id.to_string())
+        .join(MANIFEST_FILE)
+}
+
+/// Reads the manifest of `app_id`. Returns `None` for folders created before manifests
+/// existed, or when the file can't be parsed.
+pub fn load(app_id: i64) -> Option<GameManifest> {
+    let path = manifest_path(app_id);
+    let json = std::fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&json)
+        .map_err(|e| eprintln!("Ignoring unreadable manifest {:?}: {}", path, e))
+        .ok()
+}
+
+/// Writes through a temporary file so a crash never leaves a truncated manifest.
+fn save(manifest: &GameManifest) -> Result<(), String> {
+    let path = manifest_path(manifest.app_id);
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, json)
+        .and_then(|_| std::fs::rename(&temp, &path))
+        .map_err(|e| format!("Failed to write manifest {:?}: {}", path, e))
+}
+
+/// Adds or replaces the entry for `executable` in the manifest of `app_id`.
+pub fn record(
+    app_id: i64,
+    name: Option<&str>,
+    executable: ManifestExecutable,
+) -> Result<(), String> {
+    let _guard = MANIFEST_LOCK.lock().unwrap();
+
+    let mut manifest = load(app_id).unwrap_or(GameManifest {
+        version: MANIFEST_VERSION,
+        app_id,
+        name: None,
+        executables: Vec::new(),
+    });
+    if let Some(name) = name {
+        manifest.name = Some(name.to_string());
+    }
+    manifest
+        .executables
+        .retain(|entry| !entry.is_same(&executable.path, &executable.filename));
+    manifest.executables.push(executable);
+
+    save(&manifest)
+}
+
+/// Drops the entry for an executable that was removed. The manifest itself is deleted
+/// once no executables are left, so the game folder can be removed.
+pub fn forget(app_id: i64, path: &str, filename: &str) {
+    let _guard = MANIFEST_LOCK.lock().unwrap();
+
+    let Some(mut manifest) = load(app_id) else {
+        return;
+    };
+    manifest
+        .executables
+        .retain(|entry| !entry.is_same(path, filename));
+
+    let result = if manifest.executables.is_empty() {
+        std::fs::remove_file(manifest_path(app_id)).map_err(|e| e.to_string())
+    } else {
+        save(&manifest)
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to update manifest of app {}: {}", app_id, e);
+    }
+}
+
+/// Looks up the entry for one executable.
+pub fn find<'a>(
+    manifest: &'a GameManifest,
+    path: &str,
+    filename: &str,
+) -> Option<&'a ManifestExecutable> {
+    manifest
+        .executables
+        .iter()
+        .find(|entry| entry.is_same(path, filename))
+}
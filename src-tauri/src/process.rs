@@ -0,0 +1,605 @@
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+use crate::{control, games, heartbeat, sanitize};
+
+/// Emitted whenever a tracked runner goes away, whether it was stopped by the app or not.
+pub const EVENT_PROCESS_EXITED: &str = "process_exited";
+/// Emitted when a runner started with a duration is stopped because its time is up.
+pub const EVENT_SESSION_COMPLETED: &str = "session_completed";
+
+/// How long a runner gets to exit after SIGTERM before it is killed outright.
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often the watcher thread checks whether its runner is still alive.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runners end their own session once `--duration` is up. The app only stops them this
+/// long after the deadline, which covers runners installed before they could.
+const DEADLINE_SLACK: Duration = Duration::from_secs(5);
+
+/// Written by the runner when it ends its own session.
+const REASON_DURATION_ELAPSED: &str = "duration_elapsed";
+
+/// Identifies a runner by the game it pretends to be and the executable it was copied to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProcessKey {
+    pub app_id: i64,
+    pub executable: String,
+}
+
+impl ProcessKey {
+    /// The executable is lowercased: the game list's names are Windows paths, and the
+    /// path case setting may change the case of the file on disk.
+    pub fn new(app_id: i64, executable: &str) -> Self {
+        ProcessKey {
+            app_id,
+            executable: executable.to_lowercase(),
+        }
+    }
+
+    /// Key of the runner for `executable_name` as the game list spells it, using the file
+    /// name installs write to disk. A name that can't be sanitised is used as is; nothing
+    /// can be installed under it, so the key matches no runner.
+    pub fn for_executable(app_id: i64, executable_name: &str) -> Self {
+        let file_name = sanitize::sanitize_file_name(executable_name)
+            .unwrap_or_else(|_| executable_name.to_string());
+        ProcessKey::new(app_id, &file_name)
+    }
+}
+
+/// A runner spawned by the app. The `Child` handle is kept so the exact process can be
+/// stopped later instead of every process that happens to share the same image name.
+struct ManagedProcess {
+    child: Child,
+    name: String,
+    started: Instant,
+    started_at: u64,
+    /// Target play time; the watcher stops the runner once it has run this long.
+    duration: Option<Duration>,
+    status_file: PathBuf,
+    /// `None` where runners have no control socket (Windows).
+    control_socket: Option<PathBuf>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProcessInfo {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub executable: String,
+    pub name: String,
+    pub pid: u32,
+    /// Unix timestamp (seconds) of when the runner was spawned.
+    pub started_at: u64,
+    pub runtime_secs: u64,
+    pub duration_secs: Option<u64>,
+    pub remaining_secs: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopMethod {
+    /// The runner had already exited before the stop request arrived.
+    AlreadyExited,
+    /// The runner was asked over its control socket and exited within the grace period.
+    Control,
+    /// SIGTERM was sent and the runner exited within the grace period (Unix).
+    Sigterm,
+    /// The runner ignored SIGTERM for the whole grace period and was sent SIGKILL (Unix).
+    Sigkill,
+    /// The runner was terminated with `TerminateProcess` (Windows).
+    Terminated,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StopOutcome {
+    pub method: StopMethod,
+    pub pid: u32,
+    pub exit_code: Option<i32>,
+    /// Signal that ended the runner, if any. Always `None` outside Unix.
+    pub signal: Option<i32>,
+}
+
+impl StopOutcome {
+    fn new(method: StopMethod, pid: u32, status: ExitStatus) -> Self {
+        StopOutcome {
+            method,
+            pid,
+            exit_code: status.code(),
+            signal: exit_signal(status),
+        }
+    }
+}
+
+fn exit_signal(status: ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    return std::os::unix::process::ExitStatusExt::signal(&status);
+    #[cfg(not(unix))]
+    return None;
+}
+
+/// Payload of [`EVENT_PROCESS_EXITED`].
+#[derive(Serialize, Clone, Debug)]
+pub struct ProcessExited {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub executable: String,
+    pub pid: u32,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub runtime_secs: u64,
+    /// `true` when the exit was requested through `stop`, `false` when the runner
+    /// crashed or was closed from its tray menu.
+    pub stopped: bool,
+    /// Why the runner says it exited (`signal`, `duration_elapsed`, `parent_exited`,
+    /// `window_closed`, `user_quit`). `None` for runners that don't report it.
+    pub reason: Option<String>,
+}
+
+/// Payload of [`EVENT_SESSION_COMPLETED`].
+#[derive(Serialize, Clone, Debug)]
+pub struct SessionCompleted {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub executable: String,
+    pub pid: u32,
+    pub duration_secs: u64,
+    /// How long the runner actually ran, measured once it had exited.
+    pub runtime_secs: u64,
+    pub stop: StopOutcome,
+}
+
+static PROCESSES: OnceCell<Mutex<HashMap<ProcessKey, ManagedProcess>>> = OnceCell::new();
+
+fn get_processes() -> &'static Mutex<HashMap<ProcessKey, ManagedProcess>> {
+    PROCESSES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn is_alive(process: &mut ManagedProcess) -> bool {
+    matches!(process.child.try_wait(), Ok(None))
+}
+
+/// Tells the frontend that the runner under `key` is gone.
+fn emit_exited(
+    handle: &AppHandle,
+    key: &ProcessKey,
+    process: &ManagedProcess,
+    status: Option<ExitStatus>,
+    stopped: bool,
+) {
+    let payload = ProcessExited {
+        app_id: key.app_id,
+        executable: key.executable.clone(),
+        pid: process.child.id(),
+        exit_code: status.and_then(|s| s.code()),
+        signal: status.and_then(exit_signal),
+        runtime_secs: process.started.elapsed().as_secs(),
+        stopped,
+        reason: heartbeat::read(&process.status_file).and_then(|status| status.reason),
+    };
+    handle
+        .emit(EVENT_PROCESS_EXITED, payload)
+        .unwrap_or_else(|e| eprintln!("Failed to emit event: {}", e));
+
+    // Every exit ends up here; a killed runner leaves its socket behind.
+    if let Some(socket) = &process.control_socket {
+        let _ = std::fs::remove_file(socket);
+    }
+}
+
+/// Passes `activity` to the runner. Runners built with the `presence` feature set it for
+/// `--app-id` themselves and clear it on exit; others ignore it.
+fn add_activity_args(command: &mut Command, activity: &runner_core::Activity) {
+    if activity.kind != runner_core::ActivityKind::Playing {
+        command
+            .arg("--activity-kind")
+            .arg(activity.kind.code().to_string());
+    }
+    let texts = [
+        ("--details", &activity.details),
+        ("--state", &activity.state),
+        ("--large-image", &activity.large_image_key),
+        ("--large-image-text", &activity.large_image_text),
+    ];
+    for (flag, text) in texts {
+        if let Some(text) = text {
+            // Inline so text starting with `-` isn't read as a flag.
+            command.arg(format!("{}={}", flag, text));
+        }
+    }
+    // The runner refuses timestamps that aren't positive.
+    if let Some(start) = activity.start_timestamp.filter(|start| *start > 0) {
+        command.arg("--start-timestamp").arg(start.to_string());
+    }
+}
+
+/// Which flags a runner is started with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunnerFlags {
+    /// Every flag of the runner bundled with this build.
+    Current,
+    /// Only `--title`, which runners from every release read. Runners exit on flags they
+    /// don't know, so this is how installs that weren't upgraded are started.
+    TitleOnly,
+}
+
+/// Spawns the runner installed at `paths` and starts tracking it.
+pub fn spawn(
+    handle: &AppHandle,
+    name: &str,
+    paths: &games::GamePaths,
+    duration: Option<Duration>,
+    activity: Option<&runner_core::Activity>,
+    flags: RunnerFlags,
+) -> Result<u32, String> {
+    let key = paths.process_key();
+    let executable_path = paths.executable.as_path();
+    let mut processes = get_processes().lock().unwrap();
+
+    if let Some(existing) = processes.get_mut(&key) {
+        if is_alive(existing) {
+            return Err(format!(
+                "{} (app {}) is already running with PID {}",
+                key.executable,
+                key.app_id,
+                existing.child.id()
+            ));
+        }
+        // Exited between two watcher ticks; report it before the entry is replaced.
+        if let Some(mut previous) = processes.remove(&key) {
+            let status = previous.child.try_wait().ok().flatten();
+            emit_exited(handle, &key, &previous, status, false);
+        }
+    }
+
+    // A leftover status file would report the previous run's state.
+    heartbeat::remove(executable_path);
+    let status_file = heartbeat::status_file_path(executable_path);
+
+    let mut command = Command::new(executable_path);
+    let control_socket = match flags {
+        RunnerFlags::Current => {
+            command
+                // Inline so a name starting with `-` isn't read as a flag.
+                .arg(format!("--title={}", name))
+                .arg("--parent-pid")
+                .arg(std::process::id().to_string())
+                .arg("--status-file")
+                .arg(&status_file);
+            // The runner refuses ids that aren't positive, which would keep it from starting.
+            if key.app_id > 0 {
+                command.arg("--app-id").arg(key.app_id.to_string());
+            }
+            if let Some(duration) = duration {
+                command
+                    .arg("--duration")
+                    .arg(duration.as_secs().max(1).to_string());
+            }
+            // Rich Presence only when an activity was asked for.
+            if let Some(activity) = activity {
+                command.arg("--presence");
+                add_activity_args(&mut command, activity);
+            }
+            let control_socket = control::socket_path();
+            if let Some(socket) = &control_socket {
+                command.arg("--control-socket").arg(socket);
+            }
+            control_socket
+        }
+        // The watcher still stops timed sessions without the runner's help.
+        RunnerFlags::TitleOnly => {
+            command.args(["--title", name]);
+            None
+        }
+    };
+
+    let child = command
+        .current_dir(&paths.folder) // Set working directory to the game folder
+        .spawn()
+        .map_err(|e| format!("Failed to start process: {}", e))?;
+
+    let pid = child.id();
+    println!(
+        "Started {:?} for app {} with PID {}",
+        executable_path, key.app_id, pid
+    );
+
+    processes.insert(
+        key.clone(),
+        ManagedProcess {
+            child,
+            name: name.to_string(),
+            started: Instant::now(),
+            started_at: unix_now(),
+            duration,
+            status_file,
+            control_socket,
+        },
+    );
+    drop(processes);
+
+    let handle = handle.clone();
+    std::thread::spawn(move || watch(handle, key, pid));
+
+    Ok(pid)
+}
+
+/// Waits for the runner with `pid` to exit on its own and emits [`EVENT_PROCESS_EXITED`],
+/// or stops it once its duration is up. Runs on its own thread so timed sessions end on
+/// schedule whether or not the webview is open.
+/// Returns quietly once the entry is removed or replaced, since `stop` reports those exits.
+fn watch(handle: AppHandle, key: ProcessKey, pid: u32) {
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+
+        let mut processes = get_processes().lock().unwrap();
+        let process = match processes.get_mut(&key) {
+            Some(process) if process.child.id() == pid => process,
+            _ => return,
+        };
+
+        let status = match process.child.try_wait() {
+            Ok(None) => {
+                // A duration too long to add the slack to never runs out.
+                let expired = process
+                    .duration
+                    .and_then(|duration| duration.checked_add(DEADLINE_SLACK))
+                    .is_some_and(|deadline| process.started.elapsed() >= deadline);
+                if !expired {
+                    continue;
+                }
+                let Some(process) = processes.remove(&key) else {
+                    return;
+                };
+                drop(processes);
+                complete_session(&handle, &key, process);
+                return;
+            }
+            Ok(Some(status)) => Some(status),
+            Err(e) => {
+                eprintln!("Failed to check status of PID {}: {}", pid, e);
+                None
+            }
+        };
+
+        if let Some(process) = processes.remove(&key) {
+            drop(processes);
+            let reason = heartbeat::read(&process.status_file).and_then(|status| status.reason);
+            if process.duration.is_some() && reason.as_deref() == Some(REASON_DURATION_ELAPSED) {
+                complete_session(&handle, &key, process);
+                return;
+            }
+            println!("PID {} for app {} exited on its own", pid, key.app_id);
+            emit_exited(&handle, &key, &process, status, false);
+        }
+        return;
+    }
+}
+
+/// Stops a runner whose duration has elapsed, unless it already exited on its own, and
+/// emits [`EVENT_SESSION_COMPLETED`].
+fn complete_session(handle: &AppHandle, key: &ProcessKey, process: ManagedProcess) {
+    let pid = process.child.id();
+    let duration_secs = process.duration.unwrap_or_default().as_secs();
+    let started = process.started;
+    println!(
+        "PID {} for app {} reached its {}s duration",
+        pid, key.app_id, duration_secs
+    );
+
+    match shutdown(handle, key, process, DEFAULT_GRACE_PERIOD) {
+        Ok(stop) => {
+            let payload = SessionCompleted {
+                app_id: key.app_id,
+                executable: key.executable.clone(),
+                pid,
+                duration_secs,
+                runtime_secs: started.elapsed().as_secs(),
+                stop,
+            };
+            handle
+                .emit(EVENT_SESSION_COMPLETED, payload)
+                .unwrap_or_else(|e| eprintln!("Failed to emit event: {}", e));
+        }
+        Err(e) => eprintln!("Failed to end timed session: {}", e),
+    }
+}
+
+/// Polls `child` until it exits or `timeout` runs out.
+fn wait_timeout(child: &mut Child, timeout: Duration) -> std::io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+/// Asks the runner to exit over its control socket, or with SIGTERM when that fails or
+/// goes unanswered, then falls back to SIGKILL once `grace_period` is over.
+#[cfg(unix)]
+fn terminate(
+    child: &mut Child,
+    control_socket: Option<&Path>,
+    grace_period: Duration,
+) -> std::io::Result<StopOutcome> {
+    let pid = child.id();
+
+    // Older runners have no socket; connecting fails right away and SIGTERM follows.
+    if let Some(socket) = control_socket {
+        match control::shutdown(socket) {
+            Ok(()) => {
+                if let Some(status) = wait_timeout(child, grace_period)? {
+                    return Ok(StopOutcome::new(StopMethod::Control, pid, status));
+                }
+                println!(
+                    "PID {} didn't exit {:?} after a control shutdown, sending SIGTERM",
+                    pid, grace_period
+                );
+            }
+            Err(e) => println!("Falling back to SIGTERM for PID {}: {}", pid, e),
+        }
+    }
+
+    // SAFETY: `pid` belongs to a child we haven't reaped yet, so it can't have been reused.
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    if let Some(status) = wait_timeout(child, grace_period)? {
+        return Ok(StopOutcome::new(StopMethod::Sigterm, pid, status));
+    }
+
+    println!(
+        "PID {} ignored SIGTERM for {:?}, sending SIGKILL",
+        pid, grace_period
+    );
+    child.kill()?;
+    let status = child.wait()?;
+    Ok(StopOutcome::new(StopMethod::Sigkill, pid, status))
+}
+
+/// Windows has no SIGTERM equivalent for a windowed process we don't own the message loop of,
+/// so the runner is terminated right away and the grace period is unused.
+#[cfg(not(unix))]
+fn terminate(
+    child: &mut Child,
+    _control_socket: Option<&Path>,
+    _grace_period: Duration,
+) -> std::io::Result<StopOutcome> {
+    let pid = child.id();
+    child.kill()?;
+    let status = child.wait()?;
+    Ok(StopOutcome::new(StopMethod::Terminated, pid, status))
+}
+
+/// Stops the runner tracked under `key` and waits for it so it doesn't linger as a zombie.
+pub fn stop(
+    handle: &AppHandle,
+    key: &ProcessKey,
+    grace_period: Duration,
+) -> Result<StopOutcome, String> {
+    let process = get_processes().lock().unwrap().remove(key);
+
+    match process {
+        Some(process) => shutdown(handle, key, process, grace_period),
+        None => Err(format!(
+            "No running process for {} (app {})",
+            key.executable, key.app_id
+        )),
+    }
+}
+
+/// Terminates a runner that has already been taken out of the map and reports its exit.
+fn shutdown(
+    handle: &AppHandle,
+    key: &ProcessKey,
+    mut process: ManagedProcess,
+    grace_period: Duration,
+) -> Result<StopOutcome, String> {
+    let pid = process.child.id();
+
+    let outcome = match process.child.try_wait() {
+        // Already exited on its own, nothing left to kill.
+        Ok(Some(status)) => StopOutcome::new(StopMethod::AlreadyExited, pid, status),
+        _ => terminate(
+            &mut process.child,
+            process.control_socket.as_deref(),
+            grace_period,
+        )
+        .map_err(|e| format!("Failed to stop process {}: {}", pid, e))?,
+    };
+
+    let status = process.child.try_wait().ok().flatten();
+    emit_exited(handle, key, &process, status, true);
+
+    Ok(outcome)
+}
+
+/// The control socket of the runner tracked under `key`.
+fn control_socket(key: &ProcessKey) -> Result<PathBuf, String> {
+    let mut processes = get_processes().lock().unwrap();
+    let process = processes.get_mut(key).ok_or_else(|| {
+        format!(
+            "No running process for {} (app {})",
+            key.executable, key.app_id
+        )
+    })?;
+    if !is_alive(process) {
+        return Err(format!(
+            "{} (app {}) has already exited",
+            key.executable, key.app_id
+        ));
+    }
+    process.control_socket.clone().ok_or_else(|| {
+        format!(
+            "{} (app {}) has no control socket",
+            key.executable, key.app_id
+        )
+    })
+}
+
+/// Asks the runner under `key` how it is doing. Unlike [`list`], uptime comes from the
+/// runner itself rather than from when the app spawned it.
+pub fn runner_status(key: &ProcessKey) -> Result<control::RunnerStatus, String> {
+    // The lock isn't held while talking to the runner, a stuck one would block `list`.
+    control::status(&control_socket(key)?)
+}
+
+/// Changes the window title of the runner under `key`.
+pub fn rename(key: &ProcessKey, title: &str) -> Result<String, String> {
+    let title = control::rename_title(&control_socket(key)?, title)?;
+    if let Some(process) = get_processes().lock().unwrap().get_mut(key) {
+        process.name = title.clone();
+    }
+    Ok(title)
+}
+
+/// Whether the runner tracked under `key` is still alive.
+pub fn is_running(key: &ProcessKey) -> bool {
+    get_processes()
+        .lock()
+        .unwrap()
+        .get_mut(key)
+        .is_some_and(is_alive)
+}
+
+/// Returns every runner that is still alive.
+pub fn list() -> Vec<ProcessInfo> {
+    let mut processes = get_processes().lock().unwrap();
+
+    processes
+        .iter_mut()
+        .filter_map(|(key, process)| is_alive(process).then_some((key, process)))
+        .map(|(key, process)| ProcessInfo {
+            app_id: key.app_id,
+            executable: key.executable.clone(),
+            name: process.name.clone(),
+            pid: process.child.id(),
+            started_at: process.started_at,
+            runtime_secs: process.started.elapsed().as_secs(),
+            duration_secs: process.duration.map(|d| d.as_secs()),
+            remaining_secs: process
+                .duration
+                .map(|d| d.saturating_sub(process.started.elapsed()).as_secs()),
+        })
+        .collect()
+}
@@ -0,0 +1,303 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::{games, install, process};
+
+/// Emitted every time a queue entry changes state, and once more when the queue drains.
+pub const EVENT_QUEUE_PROGRESS: &str = "queue_progress";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A game to play as part of the queue, as sent by the frontend.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct QueueEntryRequest {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub name: String,
+    pub path: String,
+    pub executable_name: String,
+    pub duration_secs: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct QueueEntry {
+    pub id: u64,
+    #[serde(flatten)]
+    pub request: QueueEntryRequest,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    Installing,
+    Started,
+    /// The runner ran for its full duration.
+    Completed,
+    /// The runner exited before its duration was up (crash, tray "Quit", manual stop).
+    ExitedEarly,
+    Skipped,
+    Failed,
+    Cancelled,
+    /// Nothing is left in the queue.
+    Finished,
+}
+
+/// Payload of [`EVENT_QUEUE_PROGRESS`].
+#[derive(Serialize, Clone, Debug)]
+pub struct QueueProgress {
+    pub entry: Option<QueueEntry>,
+    pub status: QueueStatus,
+    pub runtime_secs: u64,
+    /// Entries still waiting after this one.
+    pub remaining: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct QueueSnapshot {
+    pub current: Option<QueueEntry>,
+    pub pending: Vec<QueueEntry>,
+}
+
+#[derive(Default)]
+struct Queue {
+    pending: VecDeque<QueueEntry>,
+    current: Option<QueueEntry>,
+    next_id: u64,
+    worker_running: bool,
+    /// Set when the current entry was stopped through `skip` or `cancel`, so the worker
+    /// doesn't report it as having exited early.
+    current_interrupted: Option<QueueStatus>,
+}
+
+impl Queue {
+    fn snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            current: self.current.clone(),
+            pending: self.pending.iter().cloned().collect(),
+        }
+    }
+}
+
+static QUEUE: OnceCell<Mutex<Queue>> = OnceCell::new();
+
+fn get_queue() -> &'static Mutex<Queue> {
+    QUEUE.get_or_init(|| Mutex::new(Queue::default()))
+}
+
+fn emit_progress(
+    handle: &AppHandle,
+    entry: Option<&QueueEntry>,
+    status: QueueStatus,
+    runtime_secs: u64,
+    error: Option<String>,
+) {
+    let remaining = get_queue().lock().unwrap().pending.len();
+    let payload = QueueProgress {
+        entry: entry.cloned(),
+        status,
+        runtime_secs,
+        remaining,
+        error,
+    };
+    handle
+        .emit(EVENT_QUEUE_PROGRESS, payload)
+        .unwrap_or_else(|e| eprintln!("Failed to emit event: {}", e));
+}
+
+/// Appends `requests` to the queue and starts the worker if it isn't running yet.
+pub fn enqueue(handle: &AppHandle, requests: Vec<QueueEntryRequest>) -> Vec<QueueEntry> {
+    let mut queue = get_queue().lock().unwrap();
+
+    let mut added = Vec::with_capacity(requests.len());
+    for request in requests {
+        queue.next_id += 1;
+        let entry = QueueEntry {
+            id: queue.next_id,
+            request,
+        };
+        queue.pending.push_back(entry.clone());
+        added.push(entry);
+    }
+
+    if !queue.worker_running && !queue.pending.is_empty() {
+        queue.worker_running = true;
+        let handle = handle.clone();
+        std::thread::spawn(move || worker(handle));
+    }
+
+    added
+}
+
+pub fn snapshot() -> QueueSnapshot {
+    get_queue().lock().unwrap().snapshot()
+}
+
+/// Reorders pending entries to follow `ids`. Entries missing from `ids` keep their relative
+/// order after the listed ones.
+pub fn reorder(ids: &[u64]) -> Result<QueueSnapshot, String> {
+    let mut queue = get_queue().lock().unwrap();
+
+    if let Some(unknown) = ids
+        .iter()
+        .find(|id| !queue.pending.iter().any(|entry| entry.id == **id))
+    {
+        return Err(format!("Queue entry {} is not pending", unknown));
+    }
+
+    queue.pending.make_contiguous().sort_by_key(|entry| {
+        ids.iter()
+            .position(|id| *id == entry.id)
+            .unwrap_or(usize::MAX)
+    });
+
+    Ok(queue.snapshot())
+}
+
+/// Removes a pending entry, or stops the current one so the worker moves on.
+pub fn skip(handle: &AppHandle, id: u64) -> Result<(), String> {
+    let mut queue = get_queue().lock().unwrap();
+
+    if let Some(current) = queue.current.clone().filter(|entry| entry.id == id) {
+        queue.current_interrupted = Some(QueueStatus::Skipped);
+        drop(queue);
+        stop_entry(handle, &current);
+        return Ok(());
+    }
+
+    let position = queue
+        .pending
+        .iter()
+        .position(|entry| entry.id == id)
+        .ok_or_else(|| format!("Queue entry {} not found", id))?;
+    let entry = queue.pending.remove(position);
+    drop(queue);
+
+    emit_progress(handle, entry.as_ref(), QueueStatus::Skipped, 0, None);
+    Ok(())
+}
+
+/// Drops every pending entry and stops the current one.
+pub fn cancel(handle: &AppHandle) {
+    let mut queue = get_queue().lock().unwrap();
+    let cancelled: Vec<QueueEntry> = queue.pending.drain(..).collect();
+    let current = queue.current.clone();
+    if current.is_some() {
+        queue.current_interrupted = Some(QueueStatus::Cancelled);
+    }
+    drop(queue);
+
+    for entry in &cancelled {
+        emit_progress(handle, Some(entry), QueueStatus::Cancelled, 0, None);
+    }
+    if let Some(current) = current {
+        stop_entry(handle, &current);
+    }
+}
+
+fn entry_key(entry: &QueueEntry) -> process::ProcessKey {
+    process::ProcessKey::for_executable(entry.request.app_id, &entry.request.executable_name)
+}
+
+fn stop_entry(handle: &AppHandle, entry: &QueueEntry) {
+    // The runner may still be installing or have exited already; the worker handles both.
+    if let Err(e) = process::stop(handle, &entry_key(entry), process::DEFAULT_GRACE_PERIOD) {
+        println!("Queue entry {}: {}", entry.id, e);
+    }
+}
+
+/// Plays queued entries one after another until the queue is empty.
+fn worker(handle: AppHandle) {
+    loop {
+        let entry = {
+            let mut queue = get_queue().lock().unwrap();
+            queue.current_interrupted = None;
+            match queue.pending.pop_front() {
+                Some(entry) => {
+                    queue.current = Some(entry.clone());
+                    entry
+                }
+                None => {
+                    queue.current = None;
+                    queue.worker_running = false;
+                    break;
+                }
+            }
+        };
+
+        run_entry(&handle, &entry);
+        get_queue().lock().unwrap().current = None;
+    }
+
+    emit_progress(&handle, None, QueueStatus::Finished, 0, None);
+}
+
+fn interrupted() -> Option<QueueStatus> {
+    get_queue().lock().unwrap().current_interrupted
+}
+
+fn run_entry(handle: &AppHandle, entry: &QueueEntry) {
+    let request = &entry.request;
+
+    if !games::is_installed(request.app_id, &request.path, &request.executable_name) {
+        emit_progress(handle, Some(entry), QueueStatus::Installing, 0, None);
+        if let Err(e) = install::install(
+            handle,
+            &request.path,
+            &request.executable_name,
+            request.app_id,
+            install::GameDetails {
+                name: Some(&request.name),
+                detectable: None,
+                segments: None,
+            },
+        ) {
+            emit_progress(
+                handle,
+                Some(entry),
+                QueueStatus::Failed,
+                0,
+                Some(e.to_string()),
+            );
+            return;
+        }
+    }
+    if let Some(status) = interrupted() {
+        emit_progress(handle, Some(entry), status, 0, None);
+        return;
+    }
+
+    let duration = Duration::from_secs(request.duration_secs);
+    // Taken before the spawn so a runner stopped by its timer always counts as completed.
+    let started = Instant::now();
+    if let Err(e) = games::launch(
+        handle,
+        &request.name,
+        &request.path,
+        &request.executable_name,
+        request.app_id,
+        Some(duration),
+        None,
+    ) {
+        emit_progress(handle, Some(entry), QueueStatus::Failed, 0, Some(e));
+        return;
+    }
+    emit_progress(handle, Some(entry), QueueStatus::Started, 0, None);
+
+    let key = entry_key(entry);
+    while process::is_running(&key) {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    let runtime = started.elapsed();
+    let status = match interrupted() {
+        Some(status) => status,
+        None if runtime >= duration => QueueStatus::Completed,
+        None => QueueStatus::ExitedEarly,
+    };
+    emit_progress(handle, Some(entry), status, runtime.as_secs(), None);
+}
@@ -0,0 +1,296 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::{games, install, process};
+
+/// Emitted with the full [`RotationState`] whenever a game is switched or its total changes.
+pub const EVENT_ROTATION_PROGRESS: &str = "rotation_progress";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How often the running game's total is pushed to the frontend while it plays.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+/// A runner that exits more than this long before its turn is over is counted as failed.
+const EARLY_EXIT_MARGIN: Duration = Duration::from_secs(5);
+/// A game whose runner fails this many turns in a row is left out of the rotation.
+const MAX_FAILED_TURNS: u32 = 3;
+
+/// A game to rotate through, as sent by the frontend.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RotationGameRequest {
+    #[serde(with = "crate::app_id")]
+    pub app_id: i64,
+    pub name: String,
+    pub path: String,
+    pub executable_name: String,
+    pub target_secs: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RotationGame {
+    #[serde(flatten)]
+    pub request: RotationGameRequest,
+    /// Total time this game has been running across all of its turns.
+    pub played_secs: u64,
+    pub completed: bool,
+    /// Set when the game could not be installed or started; it is left out of later turns.
+    pub error: Option<String>,
+    /// Turns in a row whose runner exited early.
+    #[serde(skip)]
+    failed_turns: u32,
+}
+
+impl RotationGame {
+    fn remaining(&self) -> u64 {
+        self.request.target_secs.saturating_sub(self.played_secs)
+    }
+
+    fn is_playable(&self) -> bool {
+        !self.completed && self.error.is_none()
+    }
+}
+
+/// Lives in the backend rather than the webview, so a reloaded UI can pick it back up
+/// through `get_rotation`.
+#[derive(Serialize, Clone, Debug)]
+pub struct RotationState {
+    pub games: Vec<RotationGame>,
+    pub interval_secs: u64,
+    /// Index into `games` of the game currently running.
+    pub current: Option<usize>,
+    pub active: bool,
+    #[serde(skip)]
+    generation: u64,
+}
+
+static ROTATION: OnceCell<Mutex<Option<RotationState>>> = OnceCell::new();
+
+fn get_rotation() -> &'static Mutex<Option<RotationState>> {
+    ROTATION.get_or_init(|| Mutex::new(None))
+}
+
+pub fn state() -> Option<RotationState> {
+    get_rotation().lock().unwrap().clone()
+}
+
+fn emit_progress(handle: &AppHandle) {
+    if let Some(state) = state() {
+        handle
+            .emit(EVENT_ROTATION_PROGRESS, state)
+            .unwrap_or_else(|e| eprintln!("Failed to emit event: {}", e));
+    }
+}
+
+/// Starts cycling through `games`, switching every `interval`. Fails if a rotation is
+/// already active.
+pub fn start(
+    handle: &AppHandle,
+    games: Vec<RotationGameRequest>,
+    interval: Duration,
+) -> Result<RotationState, String> {
+    if games.is_empty() {
+        return Err("Rotation needs at least one game".to_string());
+    }
+    if interval.is_zero() {
+        return Err("Rotation interval must be greater than zero".to_string());
+    }
+
+    let mut rotation = get_rotation().lock().unwrap();
+    if rotation.as_ref().is_some_and(|state| state.active) {
+        return Err("A rotation is already running".to_string());
+    }
+
+    let generation = rotation.as_ref().map_or(0, |state| state.generation) + 1;
+    let state = RotationState {
+        games: games
+            .into_iter()
+            .map(|request| RotationGame {
+                completed: request.target_secs == 0,
+                request,
+                played_secs: 0,
+                error: None,
+                failed_turns: 0,
+            })
+            .collect(),
+        interval_secs: interval.as_secs(),
+        current: None,
+        active: true,
+        generation,
+    };
+    *rotation = Some(state.clone());
+    drop(rotation);
+
+    let handle = handle.clone();
+    std::thread::spawn(move || worker(handle, generation, interval));
+
+    Ok(state)
+}
+
+/// Stops the rotation and the game it is currently running. Totals are kept so the
+/// frontend can still show them.
+pub fn stop(handle: &AppHandle) {
+    let current = {
+        let mut rotation = get_rotation().lock().unwrap();
+        let Some(state) = rotation.as_mut() else {
+            return;
+        };
+        state.active = false;
+        state
+            .current
+            .map(|index| state.games[index].request.clone())
+    };
+
+    if let Some(game) = current {
+        let key = process::ProcessKey::for_executable(game.app_id, &game.executable_name);
+        if let Err(e) = process::stop(handle, &key, process::DEFAULT_GRACE_PERIOD) {
+            println!("Rotation: {}", e);
+        }
+    }
+    emit_progress(handle);
+}
+
+/// Runs `f` on the rotation state if it still belongs to `generation`, i.e. no newer
+/// rotation has replaced it.
+fn with_state<T>(generation: u64, f: impl FnOnce(&mut RotationState) -> T) -> Option<T> {
+    let mut rotation = get_rotation().lock().unwrap();
+    rotation
+        .as_mut()
+        .filter(|state| state.generation == generation)
+        .map(f)
+}
+
+/// Picks the next unfinished game after `after`, wrapping around.
+fn next_game(state: &RotationState, after: Option<usize>) -> Option<usize> {
+    let count = state.games.len();
+    let start = after.map_or(0, |index| index + 1);
+    (0..count)
+        .map(|offset| (start + offset) % count)
+        .find(|index| state.games[*index].is_playable())
+}
+
+fn worker(handle: AppHandle, generation: u64, interval: Duration) {
+    let mut previous = None;
+
+    loop {
+        let turn = with_state(generation, |state| {
+            if !state.active {
+                return None;
+            }
+            let index = next_game(state, previous)?;
+            state.current = Some(index);
+            let game = &state.games[index];
+            // Never hand out more time than the game still needs.
+            let slice = interval.min(Duration::from_secs(game.remaining()));
+            Some((index, game.request.clone(), game.played_secs, slice))
+        });
+        let Some(Some((index, game, played_before, slice))) = turn else {
+            break;
+        };
+        previous = Some(index);
+        emit_progress(&handle);
+
+        let played = play_turn(&handle, generation, index, &game, slice);
+
+        // Recorded even after `stop`, so an interrupted turn still counts towards the total.
+        let recorded = with_state(generation, |state| {
+            // Cut short by `stop` rather than by the runner itself.
+            let stopped = !state.active;
+            let entry = &mut state.games[index];
+            match played {
+                Ok(played) => {
+                    entry.played_secs = (played_before + played.as_secs()).min(game.target_secs);
+                    entry.completed = entry.remaining() == 0;
+                    // A runner that crashes or refuses its arguments would otherwise be
+                    // respawned every poll, forever.
+                    if !stopped && played + EARLY_EXIT_MARGIN < slice {
+                        entry.failed_turns += 1;
+                        if entry.failed_turns >= MAX_FAILED_TURNS {
+                            entry.error = Some(format!(
+                                "Runner exited early {} turns in a row",
+                                entry.failed_turns
+                            ));
+                        }
+                    } else {
+                        entry.failed_turns = 0;
+                    }
+                }
+                Err(e) => entry.error = Some(e),
+            }
+            state.current = None;
+        });
+        if recorded.is_none() {
+            return;
+        }
+        emit_progress(&handle);
+    }
+
+    let ended = with_state(generation, |state| {
+        state.active = false;
+        state.current = None;
+    });
+    if ended.is_some() {
+        emit_progress(&handle);
+    }
+}
+
+/// Runs one game for up to `slice` and returns how long it actually ran.
+fn play_turn(
+    handle: &AppHandle,
+    generation: u64,
+    index: usize,
+    game: &RotationGameRequest,
+    slice: Duration,
+) -> Result<Duration, String> {
+    if !games::is_installed(game.app_id, &game.path, &game.executable_name) {
+        let details = install::GameDetails {
+            name: Some(&game.name),
+            detectable: None,
+            segments: None,
+        };
+        install::install(
+            handle,
+            &game.path,
+            &game.executable_name,
+            game.app_id,
+            details,
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    // `stop` may have landed while the game was installing.
+    if with_state(generation, |state| state.active) != Some(true) {
+        return Ok(Duration::ZERO);
+    }
+
+    let started = Instant::now();
+    games::launch(
+        handle,
+        &game.name,
+        &game.path,
+        &game.executable_name,
+        game.app_id,
+        Some(slice),
+        None,
+    )?;
+
+    let key = process::ProcessKey::for_executable(game.app_id, &game.executable_name);
+    let played_before = state().map_or(0, |state| state.games[index].played_secs);
+    let mut last_progress = Instant::now();
+    while process::is_running(&key) {
+        std::thread::sleep(POLL_INTERVAL);
+
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            let live = with_state(generation, |state| {
+                state.games[index].played_secs =
+                    (played_before + started.elapsed().as_secs()).min(game.target_secs);
+            });
+            if live.is_some() {
+                emit_progress(handle);
+            }
+        }
+    }
+
+    Ok(started.elapsed().min(slice))
+}
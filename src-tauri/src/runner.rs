@@ -36,57 +36,47 @@ pub fn parse_activity_json(activity_json: &str) -> Result<ActivityParams, String
     })
 }
 
-pub fn create_activity(activity_json: String) -> Result<CreateActivityResult, String> {
-    let activity: ActivityParams = parse_activity_json(&activity_json)?;
-
-    let app_id: u64 = to_app_id(&activity.app_id).map_err(|e| {
+/// The app id and activity described by `params`, as runners set them with `--presence`.
+pub fn runner_activity(params: &ActivityParams) -> Result<(u64, runner_core::Activity), String> {
+    let app_id: u64 = to_app_id(&params.app_id).map_err(|e| {
         eprintln!("Failed to parse app_id: {}", e);
         format!("Failed to parse app_id: {}", e)
     })?;
-
-    let details = activity.details.unwrap_or_default();
-    let state = activity.state.unwrap_or_default();
-    let large_image_key = activity.large_image_key.unwrap_or_default();
-    let large_image_text = activity.large_image_text;
-    let timestamp = activity.timestamp;
-    let activity_kind = activity.activity_kind.unwrap_or(0);
-
-    let mut rp: discord_sdk::activity::ActivityBuilder =
-        rpc::ds::activity::ActivityBuilder::default();
-
-    if Some(activity_kind) != None {
-        if activity_kind == 0 {
-            rp = rp.kind(rpc::ds::activity::ActivityKind::Playing);
-        } else if activity_kind == 2 {
-            rp = rp.kind(rpc::ds::activity::ActivityKind::Listening);
-        } else if activity_kind == 3 {
-            rp = rp.kind(rpc::ds::activity::ActivityKind::Watching);
-        } else if activity_kind == 5 {
-            rp = rp.kind(rpc::ds::activity::ActivityKind::Competing);
-        } else {
-            rp = rp.kind(rpc::ds::activity::ActivityKind::Playing);
-        }
+    let activity = runner_core::Activity {
+        kind: runner_core::ActivityKind::from_code(params.activity_kind.unwrap_or(0)),
+        details: params.details.clone(),
+        state: params.state.clone(),
+        large_image_key: params.large_image_key.clone(),
+        large_image_text: params.large_image_text.clone(),
+        start_timestamp: params.timestamp,
     }
+    .normalized();
+    Ok((app_id, activity))
+}
 
-    // details
-    if !details.is_empty() {
+pub fn create_activity(activity_json: String) -> Result<CreateActivityResult, String> {
+    let params: ActivityParams = parse_activity_json(&activity_json)?;
+
+    let (app_id, activity) = runner_activity(&params)?;
+    let kind = match activity.kind {
+        runner_core::ActivityKind::Playing => ActivityKind::Playing,
+        runner_core::ActivityKind::Listening => ActivityKind::Listening,
+        runner_core::ActivityKind::Watching => ActivityKind::Watching,
+        runner_core::ActivityKind::Competing => ActivityKind::Competing,
+    };
+    let mut rp = ActivityBuilder::default().kind(kind);
+
+    if let Some(details) = activity.details {
         rp = rp.details(details);
     }
-
-    // state
-    if !state.is_empty() {
+    if let Some(state) = activity.state {
         rp = rp.state(state);
     }
-
-    // timestamp
-    if let Some(ts) = timestamp {
-        rp = rp.start_timestamp(ts as i64);
+    if let Some(ts) = activity.start_timestamp {
+        rp = rp.start_timestamp(ts);
     }
-
-    // large_image_key
-    if !large_image_key.is_empty() {
-        rp = rp
-            .assets(rpc::ds::activity::Assets::default().large(&large_image_key, large_image_text));
+    if let Some(key) = activity.large_image_key {
+        rp = rp.assets(rpc::ds::activity::Assets::default().large(&key, activity.large_image_text));
     }
 
     Ok(CreateActivityResult {
@@ -0,0 +1,513 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Names Windows reserves for devices, with or without an extension (`nul.txt` is still `NUL`).
+const RESERVED_NAMES: [&str; 24] = [
+    "CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$", "COM1", "COM2", "COM3", "COM4", "COM5",
+    "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8",
+    "LPT9",
+];
+
+const INVALID_CHARACTERS: [char; 7] = ['<', '>', ':', '"', '|', '?', '*'];
+
+/// How the case of the game list's paths is carried over to disk.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PathCase {
+    /// Keep the case as written in the game list.
+    #[default]
+    Preserve,
+    /// Lowercase every folder and the file name, matching the lowercase Windows paths in
+    /// the detectable list on case-sensitive filesystems.
+    Lowercase,
+}
+
+/// Which value from the game list a rejected component came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathField {
+    Path,
+    ExecutableName,
+}
+
+impl fmt::Display for PathField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathField::Path => write!(f, "path"),
+            PathField::ExecutableName => write!(f, "executable name"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanitizeError {
+    NonUtf8 {
+        field: PathField,
+    },
+    Empty {
+        field: PathField,
+    },
+    Absolute {
+        field: PathField,
+        value: String,
+    },
+    DrivePrefix {
+        field: PathField,
+        component: String,
+    },
+    ParentDir {
+        field: PathField,
+        value: String,
+    },
+    ReservedName {
+        field: PathField,
+        component: String,
+    },
+    InvalidCharacter {
+        field: PathField,
+        component: String,
+        character: char,
+    },
+    TrailingDotOrSpace {
+        field: PathField,
+        component: String,
+    },
+    /// The executable name contains a separator and would create extra folders.
+    NotAFileName {
+        value: String,
+    },
+    /// The joined path ended up outside the game folder anyway.
+    Escapes {
+        path: PathBuf,
+        root: PathBuf,
+    },
+    /// The folder would be nested differently than the game list entry describes.
+    DepthMismatch {
+        segments: usize,
+        folders: usize,
+    },
+}
+
+impl fmt::Display for SanitizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanitizeError::NonUtf8 { field } => write!(f, "The {} is not valid UTF-8", field),
+            SanitizeError::Empty { field } => write!(f, "The {} is empty", field),
+            SanitizeError::Absolute { field, value } => {
+                write!(f, "The {} {:?} is absolute", field, value)
+            }
+            SanitizeError::DrivePrefix { field, component } => {
+                write!(f, "The {} has a drive prefix {:?}", field, component)
+            }
+            SanitizeError::ParentDir { field, value } => {
+                write!(f, "The {} {:?} contains a \"..\" component", field, value)
+            }
+            SanitizeError::ReservedName { field, component } => write!(
+                f,
+                "The {} component {:?} is a reserved Windows name",
+                field, component
+            ),
+            SanitizeError::InvalidCharacter {
+                field,
+                component,
+                character,
+            } => write!(
+                f,
+                "The {} component {:?} contains the invalid character {:?}",
+                field, component, character
+            ),
+            SanitizeError::TrailingDotOrSpace { field, component } => write!(
+                f,
+                "The {} component {:?} ends with a dot or a space",
+                field, component
+            ),
+            SanitizeError::NotAFileName { value } => {
+                write!(
+                    f,
+                    "The executable name {:?} contains a path separator",
+                    value
+                )
+            }
+            SanitizeError::Escapes { path, root } => {
+                write!(f, "{:?} is outside of {:?}", path, root)
+            }
+            SanitizeError::DepthMismatch { segments, folders } => write!(
+                f,
+                "The executable has {} path segments but would be installed {} folders deep",
+                segments, folders
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SanitizeError {}
+
+/// A relative path from the game list that is safe to join onto a game folder.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SanitizedPath {
+    pub components: Vec<String>,
+    /// Human readable notes about what was changed to make the input usable,
+    /// e.g. translated separators or dropped `.` components.
+    pub changes: Vec<String>,
+}
+
+impl SanitizedPath {
+    pub fn to_path_buf(&self) -> PathBuf {
+        self.components.iter().collect()
+    }
+
+    pub fn with_case(mut self, case: PathCase) -> Self {
+        if case == PathCase::Lowercase {
+            let lowered: Vec<String> = self.components.iter().map(|c| c.to_lowercase()).collect();
+            if lowered != self.components {
+                self.changes.push("Lowercased the path".to_string());
+                self.components = lowered;
+            }
+        }
+        self
+    }
+}
+
+/// Applies `case` to a file name returned by [`sanitize_file_name`].
+pub fn file_name_with_case(file_name: String, case: PathCase) -> String {
+    match case {
+        PathCase::Preserve => file_name,
+        PathCase::Lowercase => file_name.to_lowercase(),
+    }
+}
+
+fn to_utf8(field: PathField, raw: &OsStr) -> Result<&str, SanitizeError> {
+    raw.to_str().ok_or(SanitizeError::NonUtf8 { field })
+}
+
+fn is_drive_prefix(component: &str) -> bool {
+    let mut chars = component.chars();
+    matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(letter), Some(':'), None) if letter.is_ascii_alphabetic()
+    )
+}
+
+fn is_reserved(component: &str) -> bool {
+    let stem = component.split('.').next().unwrap_or(component).trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Checks a single file or folder name against what Windows accepts, since the game list
+/// describes Windows paths even when installing on another platform.
+fn check_component(field: PathField, component: &str) -> Result<(), SanitizeError> {
+    if let Some(character) = component
+        .chars()
+        .find(|c| INVALID_CHARACTERS.contains(c) || c.is_control())
+    {
+        return Err(SanitizeError::InvalidCharacter {
+            field,
+            component: component.to_string(),
+            character,
+        });
+    }
+    if component.ends_with('.') || component.ends_with(' ') {
+        return Err(SanitizeError::TrailingDotOrSpace {
+            field,
+            component: component.to_string(),
+        });
+    }
+    if is_reserved(component) {
+        return Err(SanitizeError::ReservedName {
+            field,
+            component: component.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Sanitises the folder part of a detectable executable (`path` in the game list).
+/// An empty path is allowed and means the executable sits directly in the game folder.
+pub fn sanitize_dir(raw: impl AsRef<OsStr>) -> Result<SanitizedPath, SanitizeError> {
+    let field = PathField::Path;
+    let value = to_utf8(field, raw.as_ref())?;
+    let mut changes = Vec::new();
+
+    if value.starts_with('/') || value.starts_with('\\') {
+        return Err(SanitizeError::Absolute {
+            field,
+            value: value.to_string(),
+        });
+    }
+    if cfg!(not(windows)) && value.contains('\\') {
+        changes.push("Translated Windows \\ separators to /".to_string());
+    }
+
+    let mut components = Vec::new();
+    for (index, component) in value.split(['/', '\\']).enumerate() {
+        match component {
+            "" => {
+                if !value.is_empty() {
+                    changes.push("Dropped an empty path component".to_string());
+                }
+            }
+            "." => changes.push("Dropped a \".\" path component".to_string()),
+            ".." => {
+                return Err(SanitizeError::ParentDir {
+                    field,
+                    value: value.to_string(),
+                })
+            }
+            _ if index == 0 && is_drive_prefix(component) => {
+                return Err(SanitizeError::DrivePrefix {
+                    field,
+                    component: component.to_string(),
+                })
+            }
+            _ => {
+                check_component(field, component)?;
+                components.push(component.to_string());
+            }
+        }
+    }
+
+    Ok(SanitizedPath {
+        components,
+        changes,
+    })
+}
+
+/// Sanitises the executable's file name, which must be a single path component.
+pub fn sanitize_file_name(raw: impl AsRef<OsStr>) -> Result<String, SanitizeError> {
+    let field = PathField::ExecutableName;
+    let value = to_utf8(field, raw.as_ref())?;
+
+    match value {
+        "" => Err(SanitizeError::Empty { field }),
+        "." | ".." => Err(SanitizeError::ParentDir {
+            field,
+            value: value.to_string(),
+        }),
+        _ if value.contains(['/', '\\']) => Err(SanitizeError::NotAFileName {
+            value: value.to_string(),
+        }),
+        _ => {
+            check_component(field, value)?;
+            Ok(value.to_string())
+        }
+    }
+}
+
+/// Joins `relative` onto `root` and makes sure the result is still inside `root`.
+pub fn contain(root: &Path, relative: &Path) -> Result<PathBuf, SanitizeError> {
+    let joined = root.join(relative);
+    let escapes = relative
+        .components()
+        .any(|component| !matches!(component, std::path::Component::Normal(_)));
+
+    if escapes || !joined.starts_with(root) {
+        return Err(SanitizeError::Escapes {
+            path: joined,
+            root: root.to_path_buf(),
+        });
+    }
+    Ok(joined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn components(raw: &str) -> Vec<String> {
+        sanitize_dir(raw).unwrap().components
+    }
+
+    #[test]
+    fn keeps_plain_paths() {
+        assert_eq!(components("Game/bin"), ["Game", "bin"]);
+        assert_eq!(components(""), Vec::<String>::new());
+        assert!(sanitize_dir("Game/bin").unwrap().changes.is_empty());
+    }
+
+    #[test]
+    fn drops_empty_and_current_dir_components() {
+        let path = sanitize_dir("./Game//bin/").unwrap();
+        assert_eq!(path.components, ["Game", "bin"]);
+        assert!(path.changes.iter().any(|change| change.contains("\".\"")));
+        assert!(path.changes.iter().any(|change| change.contains("empty")));
+    }
+
+    #[test]
+    fn translates_backslashes() {
+        let path = sanitize_dir("Game\\win64").unwrap();
+        assert_eq!(path.components, ["Game", "win64"]);
+        assert_eq!(
+            path.changes
+                .iter()
+                .any(|change| change.contains("separators")),
+            cfg!(not(windows))
+        );
+    }
+
+    #[test]
+    fn rejects_parent_dir_traversal() {
+        for raw in ["..", "../Game", "Game/../../etc", "Game\\..\\..\\Windows"] {
+            assert!(
+                matches!(sanitize_dir(raw), Err(SanitizeError::ParentDir { .. })),
+                "{:?} should be rejected",
+                raw
+            );
+        }
+        assert!(matches!(
+            sanitize_file_name(".."),
+            Err(SanitizeError::ParentDir { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_absolute_and_prefixed_paths() {
+        for raw in [
+            "/etc",
+            "\\Windows",
+            "\\\\server\\share\\game",
+            "//server/share",
+        ] {
+            assert!(
+                matches!(sanitize_dir(raw), Err(SanitizeError::Absolute { .. })),
+                "{:?} should be absolute",
+                raw
+            );
+        }
+        for raw in ["C:", "C:/Games", "c:\\Games"] {
+            assert!(
+                matches!(sanitize_dir(raw), Err(SanitizeError::DrivePrefix { .. })),
+                "{:?} should have a drive prefix",
+                raw
+            );
+        }
+        // A drive-relative path is caught by its colon.
+        assert!(matches!(
+            sanitize_dir("C:Games"),
+            Err(SanitizeError::InvalidCharacter { character: ':', .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_reserved_device_names() {
+        for raw in [
+            "CON",
+            "nul",
+            "Aux.txt",
+            "com1",
+            "LPT9.log",
+            "CONIN$",
+            "conout$.exe",
+        ] {
+            assert!(
+                matches!(sanitize_dir(raw), Err(SanitizeError::ReservedName { .. })),
+                "{:?} should be reserved",
+                raw
+            );
+            assert!(
+                matches!(
+                    sanitize_file_name(raw),
+                    Err(SanitizeError::ReservedName { .. })
+                ),
+                "{:?} should be reserved",
+                raw
+            );
+        }
+        assert_eq!(components("Console/COM10"), ["Console", "COM10"]);
+    }
+
+    #[test]
+    fn rejects_trailing_dot_or_space() {
+        for raw in ["Game.", "Game ", "Game./bin"] {
+            assert!(
+                matches!(
+                    sanitize_dir(raw),
+                    Err(SanitizeError::TrailingDotOrSpace { .. })
+                ),
+                "{:?} should be rejected",
+                raw
+            );
+        }
+        assert!(matches!(
+            sanitize_file_name("game.exe "),
+            Err(SanitizeError::TrailingDotOrSpace { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_characters() {
+        assert!(matches!(
+            sanitize_dir("Game?/bin"),
+            Err(SanitizeError::InvalidCharacter { character: '?', .. })
+        ));
+        assert!(matches!(
+            sanitize_file_name("game\u{1}.exe"),
+            Err(SanitizeError::InvalidCharacter { .. })
+        ));
+    }
+
+    #[test]
+    fn file_names_are_single_components() {
+        assert_eq!(sanitize_file_name("Game.exe").unwrap(), "Game.exe");
+        assert_eq!(
+            sanitize_file_name(""),
+            Err(SanitizeError::Empty {
+                field: PathField::ExecutableName
+            })
+        );
+        for raw in ["bin/game.exe", "bin\\game.exe"] {
+            assert!(matches!(
+                sanitize_file_name(raw),
+                Err(SanitizeError::NotAFileName { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn applies_path_case() {
+        let preserved = sanitize_dir("Game/Win64")
+            .unwrap()
+            .with_case(PathCase::Preserve);
+        assert_eq!(preserved.components, ["Game", "Win64"]);
+        assert!(preserved.changes.is_empty());
+
+        let lowered = sanitize_dir("Game/Win64")
+            .unwrap()
+            .with_case(PathCase::Lowercase);
+        assert_eq!(lowered.components, ["game", "win64"]);
+        assert_eq!(lowered.changes, ["Lowercased the path"]);
+
+        let already_lower = sanitize_dir("game").unwrap().with_case(PathCase::Lowercase);
+        assert!(already_lower.changes.is_empty());
+
+        assert_eq!(
+            file_name_with_case("Game.EXE".to_string(), PathCase::Preserve),
+            "Game.EXE"
+        );
+        assert_eq!(
+            file_name_with_case("Game.EXE".to_string(), PathCase::Lowercase),
+            "game.exe"
+        );
+    }
+
+    #[test]
+    fn contain_keeps_paths_inside_the_root() {
+        let root = Path::new("/games/1");
+        assert_eq!(
+            contain(root, Path::new("Game/bin")).unwrap(),
+            PathBuf::from("/games/1/Game/bin")
+        );
+        for relative in ["../2", "/etc", "Game/../../2", "./Game"] {
+            assert!(
+                matches!(
+                    contain(root, Path::new(relative)),
+                    Err(SanitizeError::Escapes { .. })
+                ),
+                "{:?} should escape",
+                relative
+            );
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::install::InstallMethod;
+use crate::sanitize::PathCase;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// User settings persisted as JSON in the app config dir.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Settings {
+    /// Where dummy games are installed. `None` picks a location automatically,
+    /// see `games::init_root`.
+    pub games_root: Option<PathBuf>,
+    /// How new installs place the runner template; falls back automatically when the
+    /// filesystem doesn't support it.
+    pub install_method: InstallMethod,
+    /// Case of the folders and file names created for the game list's paths. Existing
+    /// installs keep the case they were created with.
+    pub path_case: PathCase,
+}
+
+struct Store {
+    path: Option<PathBuf>,
+    settings: Settings,
+}
+
+static SETTINGS: OnceCell<Mutex<Store>> = OnceCell::new();
+
+fn get_store() -> &'static Mutex<Store> {
+    SETTINGS.get_or_init(|| {
+        Mutex::new(Store {
+            path: None,
+            settings: Settings::default(),
+        })
+    })
+}
+
+/// Loads the settings file. A missing or unreadable file falls back to defaults so a bad
+/// edit never keeps the app from starting.
+pub fn init(handle: &AppHandle) {
+    let path = match handle.path().app_config_dir() {
+        Ok(dir) => Some(dir.join(SETTINGS_FILE)),
+        Err(e) => {
+            eprintln!(
+                "Failed to resolve config dir, settings won't be saved: {}",
+                e
+            );
+            None
+        }
+    };
+
+    let settings = path
+        .as_ref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| eprintln!("Failed to parse settings, using defaults: {}", e))
+                .ok()
+        })
+        .unwrap_or_default();
+
+    let mut store = get_store().lock().unwrap();
+    store.path = path;
+    store.settings = settings;
+}
+
+pub fn get() -> Settings {
+    get_store().lock().unwrap().settings.clone()
+}
+
+/// Applies `change` and writes the result to disk.
+pub fn update(change: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+    let mut store = get_store().lock().unwrap();
+    let mut settings = store.settings.clone();
+    change(&mut settings);
+
+    if let Some(path) = &store.path {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config folder: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+
+    store.settings = settings.clone();
+    Ok(settings)
+}
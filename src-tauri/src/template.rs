@@ -0,0 +1,157 @@
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{path::BaseDirectory, AppHandle, Manager};
+
+/// A runner template bundled with the app, as recorded by `build.rs`.
+pub struct TemplateEntry {
+    pub name: &'static str,
+    /// Path relative to the resource dir.
+    pub resource: &'static str,
+    /// `None` when the template was missing at build time.
+    pub sha256: Option<&'static str>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/templates.rs"));
+
+/// Template used for new installs on this platform.
+#[cfg(target_os = "linux")]
+pub const CURRENT_TEMPLATE: &str = "linux";
+#[cfg(not(target_os = "linux"))]
+pub const CURRENT_TEMPLATE: &str = "windows";
+
+#[derive(Debug)]
+pub enum TemplateError {
+    Unknown {
+        name: String,
+    },
+    /// The build had no template to hash, so there's nothing to verify against.
+    NotBundled {
+        name: &'static str,
+    },
+    Unresolvable {
+        resource: &'static str,
+        reason: String,
+    },
+    Missing {
+        path: PathBuf,
+    },
+    Unreadable {
+        path: PathBuf,
+        reason: String,
+    },
+    Corrupt {
+        path: PathBuf,
+        expected: &'static str,
+        actual: String,
+    },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Unknown { name } => write!(f, "Unknown runner template {:?}", name),
+            TemplateError::NotBundled { name } => write!(
+                f,
+                "Runner template {:?} was not bundled with this build",
+                name
+            ),
+            TemplateError::Unresolvable { resource, reason } => write!(
+                f,
+                "Failed to resolve runner template {:?}: {}",
+                resource, reason
+            ),
+            TemplateError::Missing { path } => {
+                write!(f, "Runner template is missing at {:?}", path)
+            }
+            TemplateError::Unreadable { path, reason } => {
+                write!(f, "Failed to read runner template {:?}: {}", path, reason)
+            }
+            TemplateError::Corrupt {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Runner template {:?} is corrupt (expected SHA-256 {}, got {}), reinstall the app",
+                path, expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// A template that exists on disk and matches its recorded hash.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub sha256: &'static str,
+}
+
+pub fn entry(name: &str) -> Result<&'static TemplateEntry, TemplateError> {
+    TEMPLATES
+        .iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| TemplateError::Unknown {
+            name: name.to_string(),
+        })
+}
+
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Resolves the template `name` from the resource dir and checks its hash.
+pub fn resolve_named(handle: &AppHandle, name: &str) -> Result<Template, TemplateError> {
+    let entry = entry(name)?;
+    let expected = entry
+        .sha256
+        .ok_or(TemplateError::NotBundled { name: entry.name })?;
+
+    let path = handle
+        .path()
+        .resolve(entry.resource, BaseDirectory::Resource)
+        .map_err(|e| TemplateError::Unresolvable {
+            resource: entry.resource,
+            reason: e.to_string(),
+        })?;
+    if !path.is_file() {
+        return Err(TemplateError::Missing { path });
+    }
+
+    let actual = sha256_file(&path).map_err(|e| TemplateError::Unreadable {
+        path: path.clone(),
+        reason: e.to_string(),
+    })?;
+    if actual != expected {
+        return Err(TemplateError::Corrupt {
+            path,
+            expected,
+            actual,
+        });
+    }
+
+    Ok(Template {
+        name: entry.name,
+        path,
+        sha256: expected,
+    })
+}
+
+/// Resolves and verifies the template for this platform.
+pub fn resolve(handle: &AppHandle) -> Result<Template, TemplateError> {
+    resolve_named(handle, CURRENT_TEMPLATE)
+}
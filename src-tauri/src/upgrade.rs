@@ -0,0 +1,104 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::games::{self, InstalledGame, SkippedGame};
+use crate::install::{self, InstallMethod};
+use crate::{manifest, process, template};
+
+/// An installed dummy executable that doesn't match the bundled runner template.
+#[derive(Serialize, Clone, Debug)]
+pub struct StaleGame {
+    #[serde(flatten)]
+    pub game: InstalledGame,
+    /// Hash of the file on disk, `None` when it couldn't be read (e.g. a dangling symlink).
+    pub sha256: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct UpgradeReport {
+    /// Upgraded files, relative to the games root.
+    pub upgraded: Vec<String>,
+    pub skipped: Vec<SkippedGame>,
+}
+
+/// Compares every installed executable against the current template. Symlinked installs
+/// are followed, so they only show up when the link no longer points at a valid template.
+pub fn find_stale(handle: &AppHandle) -> Result<Vec<StaleGame>, String> {
+    let template = template::resolve(handle).map_err(|e| e.to_string())?;
+
+    let mut stale = Vec::new();
+    for game in games::list_installed()? {
+        let sha256 = template::sha256_file(&games::installed_path(&game)).ok();
+        if sha256.as_deref() != Some(template.sha256) {
+            stale.push(StaleGame { game, sha256 });
+        }
+    }
+    Ok(stale)
+}
+
+/// Whether the executable at `paths` is the runner bundled with this build, going by the
+/// template hash its manifest entry recorded at install time so launching hashes nothing.
+/// Symlinks always run the bundled file. Installs without an entry predate manifests, so
+/// they are older than the current runner too. When the bundled template is unknown, the
+/// install is assumed current.
+pub fn is_current(paths: &games::GamePaths) -> bool {
+    let folder = paths.sanitized.components.join("/");
+    let Some(manifest) = manifest::load(paths.app_id) else {
+        return false;
+    };
+    let Some(entry) = manifest::find(&manifest, &folder, &paths.file_name()) else {
+        return false;
+    };
+    if entry.method == InstallMethod::Symlink {
+        return true;
+    }
+    match template::entry(&entry.template).map(|template| template.sha256) {
+        Ok(Some(sha256)) => entry.template_sha256 == sha256,
+        Ok(None) => {
+            eprintln!(
+                "Runner template {:?} was not bundled with this build; assuming {:?} is current",
+                entry.template, paths.executable
+            );
+            true
+        }
+        Err(e) => {
+            eprintln!("{}; assuming {:?} is current", e, paths.executable);
+            true
+        }
+    }
+}
+
+/// Replaces every stale executable with the current template, skipping running ones.
+pub fn upgrade(handle: &AppHandle) -> Result<UpgradeReport, String> {
+    let mut report = UpgradeReport::default();
+
+    for StaleGame { game, .. } in find_stale(handle)? {
+        let key = process::ProcessKey::new(game.app_id, &game.filename);
+        if process::is_running(&key) {
+            report.skipped.push(SkippedGame {
+                app_id: game.app_id.to_string(),
+                reason: format!("{} is running", game.filename),
+            });
+            continue;
+        }
+
+        let details = install::GameDetails {
+            name: None,
+            detectable: game.detectable.as_deref(),
+            segments: None,
+        };
+        match install::reinstall(handle, &game, details) {
+            Ok(path) => {
+                println!("Upgraded {:?}", path);
+                report.upgraded.push(games::relative_to_root(&path));
+            }
+            // Also covers a runner started after the check above.
+            Err(e) => report.skipped.push(SkippedGame {
+                app_id: game.app_id.to_string(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(report)
+}
@@ -2,15 +2,16 @@
 
 use windows::Win32::Foundation::{COLORREF, HINSTANCE, HWND, LPARAM, LRESULT, RECT, SIZE, WPARAM};
 use windows::Win32::UI::WindowsAndMessaging::{
-    CreateWindowExA, DefWindowProcA, DispatchMessageA, GetClientRect, GetMessageA, GetWindowLongPtrA, PostQuitMessage, RegisterClassA, SetWindowLongPtrA, SetWindowPos, ShowWindow, TranslateMessage, CW_USEDEFAULT, GWL_EXSTYLE, HMENU, MSG, SWP_NOZORDER, SW_HIDE, SW_SHOWNOACTIVATE, SW_SHOWNORMAL, WINDOW_EX_STYLE, WINDOW_STYLE, WM_COMMAND, WM_CTLCOLORSTATIC, WM_DESTROY, WM_SIZE, WNDCLASSA, WS_CHILD, WS_EX_APPWINDOW, WS_EX_LAYERED, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT, WS_OVERLAPPEDWINDOW, WS_VISIBLE
+    CreateWindowExA, DefWindowProcA, DispatchMessageA, GetClientRect, GetMessageA, GetWindowLongPtrA, PostMessageA, PostQuitMessage, RegisterClassA, SetWindowLongPtrA, SetWindowPos, ShowWindow, TranslateMessage, CW_USEDEFAULT, GWL_EXSTYLE, HMENU, MSG, SWP_NOZORDER, SW_HIDE, SW_SHOWNOACTIVATE, SW_SHOWNORMAL, WINDOW_EX_STYLE, WINDOW_STYLE, WM_CLOSE, WM_COMMAND, WM_CTLCOLORSTATIC, WM_DESTROY, WM_SIZE, WNDCLASSA, WS_CHILD, WS_EX_APPWINDOW, WS_EX_LAYERED, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT, WS_OVERLAPPEDWINDOW, WS_VISIBLE
 };
 use windows::Win32::UI::Shell::ShellExecuteW;
 use windows::Win32::Graphics::Gdi::{GetDC, GetStockObject, GetTextExtentPoint32A, ReleaseDC, SetBkMode, HDC, NULL_BRUSH, TRANSPARENT};
 use windows::Win32::System::LibraryLoader::{GetModuleHandleA};
 use windows::core::{PCSTR};
 use std::ffi::CString;
-use std::env;
+use runner_core::{Action, Config, ExitReason, Lifecycle, MenuCommand, RunnerFiles, Session, Shutdown};
 
+mod parent;
 mod tray;
 use tray::create_tray_icon;
 
@@ -21,49 +22,6 @@ const LINK_BUTTON_ID: i32 = 1001;
 static mut TITLE_LABEL: Option<HWND> = None;
 static mut LINK_LABEL: Option<HWND> = None;
 
-#[derive(Debug)]
-struct Config {
-    title: String,
-    start_minimized: bool,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Config {
-            title: "Discord Quest Completer".to_string(),
-            start_minimized: false,
-        }
-    }
-}
-
-fn parse_args() -> Config {
-    let args: Vec<String> = env::args().collect();
-    let mut config = Config::default();
-    
-    let mut i = 1; // Skip program name
-    while i < args.len() {
-        match args[i].as_str() {
-            "--title" => {
-                if i + 1 < args.len() {
-                    config.title = args[i + 1].clone();
-                    i += 2;
-                } else {
-                    i += 1;
-                }
-            }
-            "--tray" => {
-                config.start_minimized = true;
-                i += 1;
-            }
-            _ => {
-                i += 1;
-            }
-        }
-    }
-    
-    config
-}
-
 fn calculate_text_width(hwnd: HWND, text: &str) -> i32 {
     unsafe {
         let hdc = GetDC(Some(hwnd));
@@ -252,8 +210,35 @@ fn create_native_window(title: &str) -> Result<(HWND, HINSTANCE), Box<dyn std::e
     }
 }
 
+// Takes the window out of the taskbar and Alt-Tab and hides it, leaving only the tray icon
+unsafe fn hide_to_tray(hwnd: HWND) {
+    let ex_style = GetWindowLongPtrA(hwnd, GWL_EXSTYLE);
+    let new_ex_style = (ex_style & !WS_EX_APPWINDOW.0 as isize) |
+        WS_EX_TOOLWINDOW.0 as isize | // Make the window a tool window (so it doesn't show in the taskbar)
+        WS_EX_TRANSPARENT.0 as isize | // Make the window transparent
+        WS_EX_LAYERED.0 as isize; // WS_EX_LAYERED make the window layered (for transparency)
+
+    SetWindowLongPtrA(hwnd, GWL_EXSTYLE, new_ex_style);
+    let _ = ShowWindow(hwnd, SW_HIDE);
+}
+
+// Restores the window styles changed by `hide_to_tray` and brings the window to the front
+unsafe fn show_from_tray(hwnd: HWND) {
+    let ex_style = GetWindowLongPtrA(hwnd, GWL_EXSTYLE);
+    let new_ex_style = (ex_style & !(WS_EX_TOOLWINDOW.0 as isize | WS_EX_TRANSPARENT.0 as isize | WS_EX_LAYERED.0 as isize)) |
+        WS_EX_APPWINDOW.0 as isize; // Restore WS_EX_APPWINDOW to show in taskbar
+
+    SetWindowLongPtrA(hwnd, GWL_EXSTYLE, new_ex_style);
+    let _ = ShowWindow(hwnd, SW_SHOWNORMAL);
+    let _ = windows::Win32::UI::WindowsAndMessaging::SetForegroundWindow(hwnd);
+}
+
 fn main() {
-    let config = parse_args();
+    let config = runner_core::from_env_or_exit(Config::default());
+    let files = RunnerFiles::create(&config);
+    let session = Session::start(config.duration);
+    let shutdown = Shutdown::new();
+    let mut lifecycle = Lifecycle::new(&config, shutdown.clone());
     
     let tray_menu = tray_icon::menu::Menu::new();
     let quit_i = tray_icon::menu::MenuItem::new("Quit", true, None);
@@ -274,10 +259,19 @@ fn main() {
     let (hwnd, instance) = match create_native_window(&config.title) {
         Ok(result) => result,
         Err(e) => {
-            eprintln!("Failed to create window: {}", e);
+            runner_core::log!("Failed to create window: {}", e);
+            files.finish(None, &session);
             return;
         }
     };
+
+    let presence = runner_core::presence::start(&config);
+
+    // Closing the window ends the message loop; HWND isn't Send, so pass the raw handle
+    let raw_hwnd = hwnd.0 as isize;
+    runner_core::watchdog::spawn(session, config.parent_pid, parent::is_alive, shutdown.clone(), move || unsafe {
+        let _ = PostMessageA(Some(HWND(raw_hwnd as *mut _)), WM_CLOSE, WPARAM(0), LPARAM(0));
+    });
     
     // Create a Windows label to display the title at the top
     let title_label_hwnd = create_label(hwnd, &config.title, instance,10,  // x position
@@ -301,19 +295,13 @@ fn main() {
     }
     
     unsafe { 
-        if config.start_minimized {
+        match lifecycle.initial_action() {
             // Only modify window styles when starting minimized
-            let ex_style = GetWindowLongPtrA(hwnd, GWL_EXSTYLE);
-            let new_ex_style = (ex_style & !WS_EX_APPWINDOW.0 as isize) |
-                WS_EX_TOOLWINDOW.0 as isize | // Make the window a tool window (so it doesn't show in the taskbar)
-                WS_EX_TRANSPARENT.0 as isize | // Make the window transparent
-                WS_EX_LAYERED.0 as isize; // WS_EX_LAYERED make the window layered (for transparency)
-            
-            SetWindowLongPtrA(hwnd, GWL_EXSTYLE, new_ex_style);
-            let _ = ShowWindow(hwnd, SW_HIDE);
-        } else {
+            Action::Hide => hide_to_tray(hwnd),
             // For normal window, just show it without modifying styles
-            let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+            _ => {
+                let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+            }
         }
         
         // Windows message loop
@@ -321,31 +309,21 @@ fn main() {
         loop {
             // Handle tray event
             if let Ok(event) = tray_icon::menu::MenuEvent::receiver().try_recv() {
-                if event.id == quit_i.id() {
-                    PostQuitMessage(0);
-                }
-
-                if event.id == show_i.id() {
-                    // Always restore window styles for normal display when showing from tray
-                    let ex_style = GetWindowLongPtrA(hwnd, GWL_EXSTYLE);
-                    let new_ex_style = (ex_style & !(WS_EX_TOOLWINDOW.0 as isize | WS_EX_TRANSPARENT.0 as isize | WS_EX_LAYERED.0 as isize)) |
-                        WS_EX_APPWINDOW.0 as isize; // Restore WS_EX_APPWINDOW to show in taskbar
-                    
-                    SetWindowLongPtrA(hwnd, GWL_EXSTYLE, new_ex_style);
-                    let _ = ShowWindow(hwnd, SW_SHOWNORMAL);
-                    let _ = windows::Win32::UI::WindowsAndMessaging::SetForegroundWindow(hwnd);
-                }
+                let command = if event.id == quit_i.id() {
+                    Some(MenuCommand::Quit)
+                } else if event.id == show_i.id() {
+                    Some(MenuCommand::Show)
+                } else if event.id == hide_i.id() {
+                    Some(MenuCommand::Hide)
+                } else {
+                    None
+                };
 
-                if event.id == hide_i.id() {
-                    // Hide window to tray (similar to --tray startup behavior)
-                    let ex_style = GetWindowLongPtrA(hwnd, GWL_EXSTYLE);
-                    let new_ex_style = (ex_style & !WS_EX_APPWINDOW.0 as isize) |
-                        WS_EX_TOOLWINDOW.0 as isize | // Make the window a tool window (so it doesn't show in the taskbar)
-                        WS_EX_TRANSPARENT.0 as isize | // Make the window transparent
-                        WS_EX_LAYERED.0 as isize; // WS_EX_LAYERED make the window layered (for transparency)
-                    
-                    SetWindowLongPtrA(hwnd, GWL_EXSTYLE, new_ex_style);
-                    let _ = ShowWindow(hwnd, SW_HIDE);
+                match command.map(|command| lifecycle.handle(command)) {
+                    Some(Action::Quit) => PostQuitMessage(0),
+                    Some(Action::Show) | Some(Action::Focus) => show_from_tray(hwnd),
+                    Some(Action::Hide) => hide_to_tray(hwnd),
+                    _ => {}
                 }
             }
 
@@ -358,4 +336,11 @@ fn main() {
             DispatchMessageA(&msg);
         }
     }
-}
\ No newline at end of file
+
+    // Only recorded if the tray menu or the watchdog didn't already end the session
+    shutdown.request(ExitReason::WindowClosed);
+    if let Some(presence) = presence {
+        presence.clear();
+    }
+    files.finish(shutdown.reason(), &session);
+}
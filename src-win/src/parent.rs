@@ -0,0 +1,15 @@
+use windows::Win32::Foundation::{CloseHandle, WAIT_TIMEOUT};
+use windows::Win32::System::Threading::{OpenProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE};
+
+// Whether the process `pid` is still running. A process that can't be opened at all is
+// treated as gone.
+pub fn is_alive(pid: u32) -> bool {
+    unsafe {
+        let Ok(handle) = OpenProcess(PROCESS_SYNCHRONIZE, false, pid) else {
+            return false;
+        };
+        let alive = WaitForSingleObject(handle, 0) == WAIT_TIMEOUT;
+        let _ = CloseHandle(handle);
+        alive
+    }
+}